@@ -1,12 +1,27 @@
-use darling::FromDeriveInput;
+use darling::{FromDeriveInput, FromField};
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
 use syn::{parse_macro_input, DeriveInput};
 
+mod serde_attributes;
+use serde_attributes::{field_name, SerdeContainer, SerdeDefault};
+
+
+#[derive(FromField)]
+#[darling(attributes(variables), forward_attrs(serde))]
+struct JsonVariablesFieldOpt {
+    ident: Option<syn::Ident>,
+    ty: syn::Type,
+    attrs: Vec<syn::Attribute>,
+    /// The field is a type deriving `JsonVariables` which may define its own variables
+    #[darling(default, with = flag)]
+    nested: bool
+}
 
 #[derive(FromDeriveInput)]
 #[darling(attributes(variables), forward_attrs(allow, doc, cfg))]
 struct JsonVariablesOpt {
+    data: darling::ast::Data<darling::util::Ignored, JsonVariablesFieldOpt>,
     /// Name of the temporary wrapper struct for deserialization
     wrapper_name: Option<syn::Ident>,
     /// Name of the variable struct for deseralization
@@ -16,8 +31,31 @@ struct JsonVariablesOpt {
     /// Field name of the variables in the JSON file
     variables_field_name: Option<String>,
     /// Flatten the data struct in the variables struct
+    #[darling(default, with = flag)]
+    flatten_data_field: bool,
+    /// Variables which have to be defined, even if they are not referenced
     #[darling(default)]
-    flatten_data_field: bool
+    require: Vec<syn::LitStr>,
+    /// Type the variables block is deserialized into before it is converted into variables
+    variables_type: Option<syn::Path>,
+    /// Function which validates the struct after the variables are replaced
+    validate: Option<syn::Path>,
+    /// Convert strings produced by placeholders into the numbers and booleans requested
+    #[darling(default, with = flag)]
+    coerce: bool,
+    /// Pattern of the placeholders instead of the default pattern
+    pattern: Option<syn::LitStr>
+}
+
+/// Parse a flag such as `coerce` or `coerce = true`, reporting any other value with a
+/// diagnostic of its own
+fn flag(meta: &syn::Meta) -> darling::Result<bool> {
+    match meta {
+        syn::Meta::Path(_) => Ok(true),
+        syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(value), .. }), .. }) => Ok(value.value),
+        syn::Meta::NameValue(name_value) => Err(darling::Error::custom("Expected `true` or `false`").with_span(&name_value.value)),
+        syn::Meta::List(list) => Err(darling::Error::custom("Expected `true` or `false`").with_span(list))
+    }
 }
 
 fn to_snake_case(value: impl Into<String>) -> String {
@@ -42,6 +80,65 @@ fn to_snake_case(value: impl Into<String>) -> String {
     snake_case_value
 }
 
+/// Implement `schemars::JsonSchema` for the templated form of the struct
+#[cfg(feature = "schemars")]
+fn json_schema(
+    struct_type: &syn::Ident,
+    struct_variable_name: &syn::Ident,
+    variables_field_name: &str,
+    data_field_name: &str,
+    flatten_data_field: bool,
+    pattern: Option<&syn::LitStr>
+) -> proc_macro2::TokenStream {
+    let data_field_name = match flatten_data_field {
+        true => quote!(::std::option::Option::None),
+        false => quote!(::std::option::Option::Some(#data_field_name))
+    };
+    let pattern = match pattern {
+        Some(pattern) => quote!(#pattern),
+        None => quote!(::json_variables::default_pattern().as_str())
+    };
+
+    // The higher ranked bound defers the check of the bound to the use of the implementation,
+    // such that structs which do not implement `JsonSchema` can still derive `JsonVariables`
+    quote!(
+        const _: () = {
+            use ::json_variables::__private::schemars;
+
+            impl schemars::JsonSchema for #struct_variable_name
+            where
+                for<'a> #struct_type: schemars::JsonSchema,
+            {
+                fn schema_name() -> ::std::borrow::Cow<'static, str> {
+                    ::std::borrow::Cow::Borrowed(stringify!(#struct_variable_name))
+                }
+
+                fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+                    let data = generator.settings()
+                        .clone()
+                        .with(|settings| settings.inline_subschemas = true)
+                        .into_generator()
+                        .into_root_schema_for::<#struct_type>();
+
+                    ::json_variables::schema::templated_schema(generator, data, #variables_field_name, #data_field_name, #pattern)
+                }
+            }
+        };
+    )
+}
+
+#[cfg(not(feature = "schemars"))]
+fn json_schema(
+    _struct_type: &syn::Ident,
+    _struct_variable_name: &syn::Ident,
+    _variables_field_name: &str,
+    _data_field_name: &str,
+    _flatten_data_field: bool,
+    _pattern: Option<&syn::LitStr>
+) -> proc_macro2::TokenStream {
+    proc_macro2::TokenStream::new()
+}
+
 #[proc_macro_derive(JsonVariables, attributes(variables))]
 pub fn derive_variables(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -75,55 +172,370 @@ pub fn derive_variables(input: TokenStream) -> TokenStream {
             false => None 
         };
 
-        return TokenStream::from(quote!(
-            use serde::de::Error;
+        let vis = &input.vis;
+
+        let serde_container = match SerdeContainer::from_attributes(&input.attrs) {
+            Ok(serde_container) => serde_container,
+            Err(err) => return err.to_compile_error().into()
+        };
+
+        // Nested fields are resolved with their own variables before the rest of the data. Their
+        // key follows the serde renaming of the struct
+        let mut nested_keys = Vec::new();
+        let mut nested_types = Vec::new();
+        let mut nested_idents = Vec::new();
+        for field in opts.data.take_struct().map(|fields| fields.fields).unwrap_or_default() {
+            if let (true, Some(ident)) = (field.nested, field.ident) {
+                match field_name(&ident, &field.attrs, serde_container.rename_all.as_deref()) {
+                    Ok(key) => nested_keys.push(key),
+                    Err(err) => return err.to_compile_error().into()
+                }
+                nested_types.push(field.ty);
+                nested_idents.push(ident);
+            }
+        }
 
+        let require = opts.require;
+
+        let coerce = match opts.coerce {
+            true => Some(quote!(variables.coerce = true;)),
+            false => None
+        };
+        let pattern = opts.pattern.as_ref().map(|pattern| quote!(
+            variables.pattern = ::json_variables::__private::pattern(#pattern)?;
+        ));
+
+        let validate = opts.validate.map(|validate| quote!(
+            #validate(self).map_err(|err| ::json_variables::Error::Validation(::std::convert::Into::into(err)))?;
+        ));
+
+        // Read the variables block, validating it through the variables type if given. A typed
+        // block is always read, such that its defaults and required fields apply
+        let read_variables = match opts.variables_type {
+            Some(variables_type) => quote!(
+                let value = value.unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
+                let variables: #variables_type = serde_json::from_value(value)?;
+                let variables: ::json_variables::Variables = serde_json::from_value(serde_json::to_value(&variables)?)?;
+                ::std::result::Result::Ok(::std::option::Option::Some(variables))
+            ),
+            None => quote!(
+                value
+                    .map(serde_json::from_value::<::json_variables::Variables>)
+                    .transpose()
+                    .map_err(::std::convert::Into::into)
+            )
+        };
+
+        // Resolve the templated top level JSON, where the data is either flattened or nested
+        // under the data field
+        let resolve_templated = match opts.flatten_data_field {
+            true => quote!(
+                match Self::resolve_data(serde_json::Value::Object(data), scope)? {
+                    serde_json::Value::Object(data) => ::std::result::Result::Ok(data),
+                    _ => ::std::result::Result::Err(::json_variables::Error::InvalidJson),
+                }
+            ),
+            false => quote!(
+                let key = match data.contains_key(#data_field_name) {
+                    true => #data_field_name,
+                    false => stringify!(#struct_name_snake_case),
+                };
+                let value = data.remove(key);
+                let mut data = match ::json_variables::__private::apply(scope, serde_json::Value::Object(data))? {
+                    serde_json::Value::Object(data) => data,
+                    _ => return ::std::result::Result::Err(::json_variables::Error::InvalidJson),
+                };
+                if let ::std::option::Option::Some(value) = value {
+                    data.insert(key.to_string(), Self::resolve_data(value, scope)?);
+                }
+                ::std::result::Result::Ok(data)
+            )
+        };
+
+        // Take the plain data out of the resolved templated JSON, following the serde container
+        // attributes of the struct for unknown and missing fields
+        let missing_data = match &serde_container.default {
+            SerdeDefault::None => quote!(
+                ::std::result::Result::Err(<serde_json::Error as serde::de::Error>::missing_field(#data_field_name).into())
+            ),
+            SerdeDefault::Default => quote!(
+                serde_json::to_value(<#struct_type as ::std::default::Default>::default()).map_err(::std::convert::Into::into)
+            ),
+            SerdeDefault::Path(path) => quote!(
+                serde_json::to_value(#path()).map_err(::std::convert::Into::into)
+            )
+        };
+        let deny_unknown_fields = match serde_container.deny_unknown_fields {
+            true => Some(quote!(
+                if let ::std::option::Option::Some(key) = data.keys().next() {
+                    return ::std::result::Result::Err(<serde_json::Error as serde::de::Error>::unknown_field(key, &[#data_field_name]).into());
+                }
+            )),
+            false => None
+        };
+        let take_data = match opts.flatten_data_field {
+            true => quote!(::std::result::Result::Ok(serde_json::Value::Object(data))),
+            false => quote!(
+                let value = data.remove(#data_field_name)
+                    .or_else(|| data.remove(stringify!(#struct_name_snake_case)));
+                #deny_unknown_fields
+                match value {
+                    ::std::option::Option::Some(value) => ::std::result::Result::Ok(value),
+                    ::std::option::Option::None => #missing_data,
+                }
+            )
+        };
+
+        // When flattened the data lives next to the variables, otherwise it is nested under the
+        // data field
+        let from_parts_data = match opts.flatten_data_field {
+            true => quote!(
+                match serde_json::to_value(target)? {
+                    serde_json::Value::Object(data) => data,
+                    _ => return ::std::result::Result::Err(::json_variables::Error::InvalidJson),
+                }
+            ),
+            false => quote!(
+                {
+                    let mut data = serde_json::Map::new();
+                    data.insert(#data_field_name.to_string(), serde_json::to_value(target)?);
+                    data
+                }
+            )
+        };
+
+        let schema = json_schema(&struct_type, &struct_variable_name, &variables_field_name, &data_field_name, opts.flatten_data_field, opts.pattern.as_ref());
+
+        return TokenStream::from(quote!(
             #[derive(Debug)]
-            pub struct #struct_variable_name {
-                variables: json_variables::Variables,
-                data: String,
+            #vis struct #struct_variable_name {
+                variables: ::json_variables::Variables,
+                data: ::json_variables::__private::serde_json::Map<::std::string::String, ::json_variables::__private::serde_json::Value>,
             }
 
-            #[derive(Debug, Deserialize, Serialize)]
-            pub struct #struct_wrapper_name {
+            #[derive(Debug, ::json_variables::__private::serde::Deserialize, ::json_variables::__private::serde::Serialize)]
+            #[serde(crate = "::json_variables::__private::serde")]
+            #vis struct #struct_wrapper_name {
                 #[serde(alias = #data_field_name)]
                 #flatten
                 #struct_name_snake_case: #struct_type
             }
 
-            impl<'de> serde::Deserialize<'de> for #struct_variable_name {
-                fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-                where
-                    D: serde::Deserializer<'de>,
-                {
-                    let mut data: serde_json::map::Map<String, serde_json::Value> = serde::Deserialize::deserialize(deserializer)?;
-                    let mut variables = json_variables::Variables::default();
-                    if let Some(var) = data.remove(#variables_field_name) {
-                        variables = serde_json::from_value(var)
-                            .map_err(D::Error::custom)?;
+            // Scope the generated impls so that several derives can live in the same module
+            const _: () = {
+                use ::json_variables::__private::serde;
+                use ::json_variables::__private::serde_json;
+
+                impl<'de> serde::Deserialize<'de> for #struct_variable_name {
+                    fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+                    where
+                        D: serde::Deserializer<'de>,
+                    {
+                        let mut data: serde_json::Map<::std::string::String, serde_json::Value> = serde::Deserialize::deserialize(deserializer)?;
+                        let variables = #struct_variable_name::read_variables(data.remove(#variables_field_name))
+                            .map_err(<D::Error as serde::de::Error>::custom)?
+                            .unwrap_or_default();
+                        let variables = #struct_variable_name::configure(variables).map_err(<D::Error as serde::de::Error>::custom)?;
+
+                        ::std::result::Result::Ok(#struct_variable_name { variables, data })
                     }
+                }
+
+                impl serde::Serialize for #struct_variable_name {
+                    fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                    where
+                        S: serde::Serializer,
+                    {
+                        use serde::ser::SerializeMap;
 
-                    let data = serde_json::to_string(&data).map_err(D::Error::custom)?;
+                        let mut map = serializer.serialize_map(::std::option::Option::Some(self.data.len() + 1))?;
+                        map.serialize_entry(#variables_field_name, &self.variables)?;
+                        for (key, value) in self.data.iter() {
+                            map.serialize_entry(key, value)?;
+                        }
 
-                    Ok(#struct_variable_name { variables, data })
+                        map.end()
+                    }
                 }
-            }
 
-            impl #struct_variable_name {
-                pub fn parse(self) -> Result<#struct_type, serde_json::Error> {
-                    let variables = self.variables;
-                    let data = self.data;
-                    let data = variables.replace(data);
-                    let data: #struct_wrapper_name = serde_json::from_str(data.as_str())?;
-                    Ok(data.#struct_name_snake_case)
+                impl #struct_variable_name {
+                    /// Create the templated form of the given value with the given variables. The
+                    /// placeholders can then be added to the data through [`Self::data_mut`]
+                    pub fn from_parts(target: &#struct_type, variables: ::json_variables::Variables) -> ::std::result::Result<Self, ::json_variables::Error> {
+                        let variables = Self::configure(variables)?;
+                        let data = #from_parts_data;
+
+                        ::std::result::Result::Ok(#struct_variable_name { variables, data })
+                    }
+
+                    /// Read the templated JSON from the file, where the relative paths of
+                    /// `${file:...}` and `${json:...}` are resolved against the directory of the file
+                    pub fn from_file(path: impl ::std::convert::AsRef<::std::path::Path>) -> ::std::result::Result<Self, ::json_variables::Error> {
+                        let path = path.as_ref();
+                        let json = ::std::fs::read_to_string(path)
+                            .map_err(|err| ::json_variables::Error::Io(path.display().to_string(), err))?;
+                        let mut templated: Self = serde_json::from_str(&json)?;
+                        templated.variables.base_dir = path.parent().map(::std::path::Path::to_path_buf);
+                        ::std::result::Result::Ok(templated)
+                    }
+
+                    /// The variables defined in the templated JSON
+                    pub fn variables(&self) -> &::json_variables::Variables {
+                        &self.variables
+                    }
+
+                    /// Mutable access to the variables defined in the templated JSON
+                    pub fn variables_mut(&mut self) -> &mut ::json_variables::Variables {
+                        &mut self.variables
+                    }
+
+                    /// The templated data, without the variables
+                    pub fn data(&self) -> &serde_json::Map<::std::string::String, serde_json::Value> {
+                        &self.data
+                    }
+
+                    /// Mutable access to the templated data, without the variables
+                    pub fn data_mut(&mut self) -> &mut serde_json::Map<::std::string::String, serde_json::Value> {
+                        &mut self.data
+                    }
+
+                    /// Replace the variables in the data and deserialize the result
+                    pub fn parse(self) -> ::std::result::Result<#struct_type, ::json_variables::Error> {
+                        Self::parse_data(&self.variables, self.data)
+                    }
+
+                    /// Replace the variables in the data and deserialize the result, where the
+                    /// given overrides take precedence over the variables defined in the JSON,
+                    /// including the variables of nested blocks. See
+                    /// [`Variables::with_top_layer`](::json_variables::Variables::with_top_layer)
+                    pub fn parse_with(self, overrides: &::json_variables::Variables) -> ::std::result::Result<#struct_type, ::json_variables::Error> {
+                        let variables = self.variables.with_top_layer(overrides.as_map(), ::json_variables::MergeStrategy::REPLACE);
+                        Self::parse_data(&variables, self.data)
+                    }
+
+                    /// Replace the variables in the data and deserialize the result, where the
+                    /// given profile of the variables is laid over the base variables. See
+                    /// [`Variables::with_profile`](::json_variables::Variables::with_profile)
+                    pub fn parse_profile(self, profile: &str) -> ::std::result::Result<#struct_type, ::json_variables::Error> {
+                        let variables = self.variables.with_profile(profile)?;
+                        Self::parse_data(&variables, self.data)
+                    }
+
+                    /// Same as [`Self::parse_with`] with the overrides given as a JSON map
+                    pub fn parse_with_map(self, overrides: serde_json::Map<::std::string::String, serde_json::Value>) -> ::std::result::Result<#struct_type, ::json_variables::Error> {
+                        let variables = self.variables.with_top_layer(&overrides, ::json_variables::MergeStrategy::REPLACE);
+                        Self::parse_data(&variables, self.data)
+                    }
+
+                    fn parse_data(variables: &::json_variables::Variables, data: serde_json::Map<::std::string::String, serde_json::Value>) -> ::std::result::Result<#struct_type, ::json_variables::Error> {
+                        ::json_variables::__private::begin_render(variables);
+                        let data = Self::resolve_templated(data, variables)?;
+                        let data = Self::take_data(data)?;
+                        let data: #struct_type = ::json_variables::__private::from_value(variables, data)?;
+                        ::json_variables::Templated::validate(&data)?;
+                        ::std::result::Result::Ok(data)
+                    }
+
+                    /// Take the plain data out of the resolved templated JSON
+                    #[allow(unused_mut)]
+                    fn take_data(mut data: serde_json::Map<::std::string::String, serde_json::Value>) -> ::std::result::Result<serde_json::Value, ::json_variables::Error> {
+                        #take_data
+                    }
+
+                    /// Apply the attributes of the derive to the variables
+                    #[allow(unused_mut)]
+                    fn configure(mut variables: ::json_variables::Variables) -> ::std::result::Result<::json_variables::Variables, ::json_variables::Error> {
+                        #coerce
+                        #pattern
+                        ::std::result::Result::Ok(variables)
+                    }
+
+                    /// Read the variables block of the templated JSON
+                    fn read_variables(value: ::std::option::Option<serde_json::Value>) -> ::std::result::Result<::std::option::Option<::json_variables::Variables>, ::json_variables::Error> {
+                        #read_variables
+                    }
+
+                    /// Replace the variables in the templated JSON without the variables block
+                    fn resolve_templated(
+                        mut data: serde_json::Map<::std::string::String, serde_json::Value>,
+                        scope: &::json_variables::Variables
+                    ) -> ::std::result::Result<serde_json::Map<::std::string::String, serde_json::Value>, ::json_variables::Error> {
+                        let missing: ::std::vec::Vec<::std::string::String> = [#(#require),*]
+                            .into_iter()
+                            .filter(|variable: &&str| !scope.contains(variable))
+                            .map(::std::string::ToString::to_string)
+                            .collect();
+                        if !missing.is_empty() {
+                            return ::std::result::Result::Err(::json_variables::Error::MissingVariables(missing));
+                        }
+
+                        #resolve_templated
+                    }
+
+                    /// Replace the variables in the plain data, resolving nested fields in their
+                    /// own scope
+                    fn resolve_data(data: serde_json::Value, scope: &::json_variables::Variables) -> ::std::result::Result<serde_json::Value, ::json_variables::Error> {
+                        let mut data = match data {
+                            serde_json::Value::Object(data) => data,
+                            data => return ::json_variables::__private::apply(scope, data),
+                        };
+
+                        #[allow(unused_mut)]
+                        let mut nested = ::std::vec::Vec::<(&str, serde_json::Value)>::new();
+                        #(
+                            if let ::std::option::Option::Some(value) = data.remove(#nested_keys) {
+                                nested.push((#nested_keys, <#nested_types as ::json_variables::Templated>::resolve_scoped(value, scope)?));
+                            }
+                        )*
+
+                        let mut data = ::json_variables::__private::apply(scope, serde_json::Value::Object(data))?;
+                        if let serde_json::Value::Object(ref mut data) = data {
+                            for (key, value) in nested {
+                                data.insert(key.to_string(), value);
+                            }
+                        }
+
+                        ::std::result::Result::Ok(data)
+                    }
                 }
-            }
+
+                impl ::json_variables::Templated for #struct_type {
+                    fn resolve_scoped(value: serde_json::Value, scope: &::json_variables::Variables) -> ::std::result::Result<serde_json::Value, ::json_variables::Error> {
+                        let mut data = match value {
+                            serde_json::Value::Object(data) => data,
+                            _ => return ::std::result::Result::Err(::json_variables::Error::InvalidJson),
+                        };
+
+                        // Variables of the nested block shadow the variables of the parent, except
+                        // for the overrides and the profile which stay on top. The attributes of
+                        // the nested struct, such as its pattern, apply inside of it
+                        let variables = match #struct_variable_name::read_variables(data.remove(#variables_field_name))? {
+                            ::std::option::Option::Some(variables) => scope.with_scope(variables.as_map()),
+                            ::std::option::Option::None => ::json_variables::__private::scope(scope),
+                        };
+                        let scope = &#struct_variable_name::configure(variables)?;
+
+                        let data = #struct_variable_name::resolve_templated(data, scope)?;
+                        #struct_variable_name::take_data(data)
+                    }
+
+                    fn validate(&self) -> ::std::result::Result<(), ::json_variables::Error> {
+                        #(
+                            <#nested_types as ::json_variables::Templated>::validate(&self.#nested_idents)?;
+                        )*
+                        #validate
+                        ::std::result::Result::Ok(())
+                    }
+                }
+            };
+
+            #schema
         ));
     }
     
     TokenStream::from(
         syn::Error::new(
-            input.ident.span(), "Only structs can derive `Variables`"
+            input.ident.span(), "Only structs can derive `JsonVariables`"
         ).to_compile_error()
     )
 }
@@ -0,0 +1,135 @@
+use syn::{Attribute, LitStr};
+
+/// The `#[serde(default)]` container attribute
+pub enum SerdeDefault {
+    None,
+    Default,
+    Path(syn::ExprPath)
+}
+
+/// The serde container attributes which change how the data of a struct is deserialized
+pub struct SerdeContainer {
+    pub deny_unknown_fields: bool,
+    pub default: SerdeDefault,
+    pub rename_all: Option<String>,
+}
+
+impl SerdeContainer {
+    pub fn from_attributes(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut container = SerdeContainer { deny_unknown_fields: false, default: SerdeDefault::None, rename_all: None };
+
+        for attribute in attributes.iter().filter(|attribute| attribute.path().is_ident("serde")) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("deny_unknown_fields") {
+                    container.deny_unknown_fields = true;
+                }
+                else if meta.path.is_ident("default") {
+                    container.default = match meta.input.peek(syn::Token![=]) {
+                        true => SerdeDefault::Path(meta.value()?.parse::<LitStr>()?.parse()?),
+                        false => SerdeDefault::Default
+                    };
+                }
+                else if meta.path.is_ident("rename_all") {
+                    container.rename_all = deserialize_name(&meta)?;
+                }
+                else {
+                    skip(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(container)
+    }
+}
+
+/// The name of a field in the JSON, following `#[serde(rename)]` and `#[serde(rename_all)]`
+pub fn field_name(ident: &syn::Ident, attributes: &[Attribute], rename_all: Option<&str>) -> syn::Result<String> {
+    let mut rename = None;
+    for attribute in attributes.iter().filter(|attribute| attribute.path().is_ident("serde")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                if let Some(name) = deserialize_name(&meta)? {
+                    rename = Some(name);
+                }
+            }
+            else {
+                skip(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    let ident = ident.to_string();
+    let ident = ident.trim_start_matches("r#");
+    if let Some(rename) = rename {
+        return Ok(rename);
+    }
+
+    match rename_all {
+        Some(rule) => apply_rename_rule(ident, rule),
+        None => Ok(ident.to_string())
+    }
+}
+
+/// Read the deserialize name of either `name = "..."` or `name(serialize = "...", deserialize = "...")`
+fn deserialize_name(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+
+    let mut name = None;
+    meta.parse_nested_meta(|meta| {
+        let value = meta.value()?.parse::<LitStr>()?.value();
+        if meta.path.is_ident("deserialize") {
+            name = Some(value);
+        }
+        Ok(())
+    })?;
+
+    Ok(name)
+}
+
+/// Skip the value of a serde attribute which is not of interest
+fn skip(meta: &syn::meta::ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    }
+    else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|meta| skip(&meta))?;
+    }
+    Ok(())
+}
+
+/// Apply a serde rename rule to a snake case field name
+fn apply_rename_rule(field: &str, rule: &str) -> syn::Result<String> {
+    let words = field.split('_').filter(|word| !word.is_empty());
+    let capitalize = |word: &str| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+            None => String::new()
+        }
+    };
+
+    let name = match rule {
+        "lowercase" => field.to_lowercase(),
+        "UPPERCASE" => field.to_uppercase(),
+        "PascalCase" => words.map(capitalize).collect(),
+        "camelCase" => {
+            let pascal: String = words.map(capitalize).collect();
+            let mut chars = pascal.chars();
+            match chars.next() {
+                Some(first) => first.to_lowercase().chain(chars).collect(),
+                None => pascal
+            }
+        }
+        "snake_case" => field.to_string(),
+        "SCREAMING_SNAKE_CASE" => field.to_uppercase(),
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.replace('_', "-").to_uppercase(),
+        rule => return Err(syn::Error::new(proc_macro2::Span::call_site(), format!("Unknown serde rename rule `{rule}`")))
+    };
+
+    Ok(name)
+}
@@ -0,0 +1,77 @@
+use serde_json::{Map, Value};
+
+use super::commands::CommandPolicy;
+use super::files::FileAccessPolicy;
+use super::functions::Functions;
+use super::variables::{self, Variables};
+
+/// Builder of [`Variables`], see [`Variables::builder`]
+#[derive(Debug, Default)]
+pub struct VariablesBuilder {
+    variables: Map<String, Value>,
+    paths: Vec<(String, Value)>,
+    pattern: Option<String>,
+    coerce: bool,
+    functions: Functions,
+    file_access: FileAccessPolicy,
+    commands: CommandPolicy
+}
+
+impl VariablesBuilder {
+    /// Define a variable, in the format var.var.var, see [`Variables::insert`]. The variables
+    /// are inserted after the maps given to [`Self::variables`].
+    pub fn variable(mut self, path: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.paths.push((path.into(), value.into()));
+        self
+    }
+
+    /// Define the top level variables of the map
+    pub fn variables(mut self, variables: Map<String, Value>) -> Self {
+        self.variables.extend(variables);
+        self
+    }
+
+    /// Use the given pattern instead of the default pattern
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// See [`Variables::coerce`]
+    pub fn coerce(mut self, coerce: bool) -> Self {
+        self.coerce = coerce;
+        self
+    }
+
+    /// See [`Variables::functions`]
+    pub fn functions(mut self, functions: Functions) -> Self {
+        self.functions = functions;
+        self
+    }
+
+    /// See [`Variables::file_access`]
+    pub fn file_access(mut self, file_access: FileAccessPolicy) -> Self {
+        self.file_access = file_access;
+        self
+    }
+
+    /// See [`Variables::commands`]
+    pub fn commands(mut self, commands: CommandPolicy) -> Self {
+        self.commands = commands;
+        self
+    }
+
+    pub fn build(self) -> Result<Variables, super::Error> {
+        let mut map = self.variables;
+        for (path, value) in self.paths {
+            variables::insert_path(&mut map, &path, value)?;
+        }
+
+        let mut variables = Variables::new(map, self.pattern)?;
+        variables.coerce = self.coerce;
+        variables.functions = self.functions;
+        variables.file_access = self.file_access;
+        variables.commands = self.commands;
+        Ok(variables)
+    }
+}
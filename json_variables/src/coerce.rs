@@ -0,0 +1,195 @@
+use serde::de::{
+    self, value::{MapDeserializer, SeqDeserializer}, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer,
+    Unexpected, VariantAccess, Visitor
+};
+use serde_json::{Map, Value};
+
+/// Key of the object which marks a string produced by a placeholder
+const MARKER: &str = "$json_variables::coerce";
+
+/// Mark a string produced by a placeholder, such that it may be coerced into the type requested
+/// during deserialization
+pub(crate) fn mark(string: String) -> Value {
+    let mut marker = Map::new();
+    marker.insert(MARKER.to_string(), Value::String(string));
+    Value::Object(marker)
+}
+
+/// Whether a string inside the value is marked for coercion
+pub(crate) fn contains_marks(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => marked(value).is_some() || map.values().any(contains_marks),
+        Value::Array(array) => array.iter().any(contains_marks),
+        _ => false
+    }
+}
+
+/// Get the string of a marked value
+fn marked(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(map) if map.len() == 1 => map.get(MARKER)?.as_str(),
+        _ => None
+    }
+}
+
+/// Deserializer of a JSON value which converts strings produced by placeholders into numbers and
+/// booleans when the deserialized type requests them. Literal strings are left untouched.
+pub(crate) struct Coerce(pub Value);
+
+impl Coerce {
+    fn marked(&self) -> Option<&str> {
+        marked(&self.0)
+    }
+
+    fn invalid<E: de::Error>(&self, expected: &dyn de::Expected) -> E {
+        E::invalid_value(Unexpected::Str(self.marked().unwrap_or_default()), expected)
+    }
+}
+
+macro_rules! coerce_number {
+    ($($method:ident => $visit:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.marked() {
+                    Some(string) => match string.trim().parse::<$ty>() {
+                        Ok(number) => visitor.$visit(number),
+                        Err(_) => Err(self.invalid(&visitor))
+                    },
+                    None => self.deserialize_any(visitor)
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Coerce {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(string) = self.marked() {
+            return visitor.visit_string(string.to_string());
+        }
+
+        match self.0 {
+            Value::Array(array) => visitor.visit_seq(SeqDeserializer::new(array.into_iter().map(Coerce))),
+            Value::Object(map) => visitor.visit_map(MapDeserializer::new(map.into_iter().map(|(key, value)| (key, Coerce(value))))),
+            value => value.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.marked() {
+            Some(string) => match string.trim().parse::<bool>() {
+                Ok(boolean) => visitor.visit_bool(boolean),
+                Err(_) => Err(self.invalid(&visitor))
+            },
+            None => self.deserialize_any(visitor)
+        }
+    }
+
+    coerce_number! {
+        deserialize_i8 => visit_i64: i64,
+        deserialize_i16 => visit_i64: i64,
+        deserialize_i32 => visit_i64: i64,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u64: u64,
+        deserialize_u16 => visit_u64: u64,
+        deserialize_u32 => visit_u64: u64,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f64: f64,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(Coerce(value))
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V
+    ) -> Result<V::Value, Self::Error> {
+        if let Some(string) = self.marked() {
+            return visitor.visit_enum(string.to_string().into_deserializer());
+        }
+
+        match self.0 {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().unwrap();
+                visitor.visit_enum(Enum { variant, value })
+            }
+            value => Err(de::Error::invalid_type(unexpected(&value), &"string or map with a single key"))
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for Coerce {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Externally tagged enum variant with data
+struct Enum {
+    variant: String,
+    value: Value
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = serde_json::Error;
+    type Variant = Coerce;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, Coerce(self.value)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Coerce {
+    type Error = serde_json::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, Self::Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+}
+
+fn unexpected(value: &Value) -> Unexpected<'_> {
+    match value {
+        Value::Null => Unexpected::Unit,
+        Value::Bool(boolean) => Unexpected::Bool(*boolean),
+        Value::Number(_) => Unexpected::Other("number"),
+        Value::String(string) => Unexpected::Str(string),
+        Value::Array(_) => Unexpected::Seq,
+        Value::Object(_) => Unexpected::Map
+    }
+}
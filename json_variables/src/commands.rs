@@ -0,0 +1,209 @@
+use std::{
+    io::Read,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant}
+};
+
+use serde_json::Value;
+
+use super::Error;
+
+/// Prefix of a placeholder which substitutes the trimmed output of a command
+pub const CMD_PREFIX: &str = "cmd:";
+
+/// The commands which can be run by `${cmd:...}` placeholders, such as
+/// `${cmd:git rev-parse --short HEAD}`.
+///
+/// Commands are disabled by default. Once enabled, only the allowed executables can be run,
+/// and a command which does not finish within the timeout is killed. The command is split into
+/// arguments, where quotes group words, and is run without a shell unless [`Self::shell`] is
+/// set. Commands run in [`Variables::base_dir`](super::Variables::base_dir) if it is set.
+/// Placeholders cannot be nested inside a command, as their values could add arguments.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use serde_json::json;
+/// use json_variables::{CommandPolicy, Error};
+///
+/// let mut variables = json_variables::from_json(json!({})).unwrap();
+/// let result = variables.apply(json!("${cmd:git rev-parse --short HEAD}"));
+/// assert!(matches!(result, Err(Error::CommandDenied(..))));
+///
+/// variables.commands = CommandPolicy::enabled()
+///     .allow("git")
+///     .timeout(Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommandPolicy {
+    enabled: bool,
+    allowed: Vec<String>,
+    timeout: Duration,
+    shell: bool
+}
+
+impl Default for CommandPolicy {
+    fn default() -> Self {
+        CommandPolicy { enabled: false, allowed: Vec::new(), timeout: Duration::from_secs(10), shell: false }
+    }
+}
+
+impl CommandPolicy {
+    /// Allow running commands, where the executables still have to be allowed through
+    /// [`Self::allow`]
+    pub fn enabled() -> Self {
+        CommandPolicy { enabled: true, ..Default::default() }
+    }
+
+    /// Allow running the executable, given by the name used in the placeholder such as `git`
+    pub fn allow(mut self, executable: impl Into<String>) -> Self {
+        self.allowed.push(executable.into());
+        self
+    }
+
+    /// Kill commands which run longer than the timeout, 10 seconds by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the commands through `sh -c`, or `cmd /C` on Windows, such that pipes and variables
+    /// of the shell can be used. Only the first word of the command is checked against the
+    /// allowed executables, so the shell can run any other command as well.
+    pub fn shell(mut self, shell: bool) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    fn command(&self, command: &str) -> Result<Command, Error> {
+        let denied = |reason: &str| Error::CommandDenied(command.to_string(), reason.to_string());
+        if !self.enabled {
+            return Err(denied("commands are disabled"));
+        }
+
+        let arguments = split(command).ok_or_else(|| denied("the command has an unterminated quote"))?;
+        let Some(executable) = arguments.first() else {
+            return Err(denied("the command is empty"));
+        };
+        if !self.allowed.contains(executable) {
+            return Err(denied(&format!("{executable} is not allowed")));
+        }
+
+        match self.shell {
+            true if cfg!(windows) => {
+                let mut process = Command::new("cmd");
+                process.args(["/C", command]);
+                Ok(process)
+            }
+            true => {
+                let mut process = Command::new("sh");
+                process.args(["-c", command]);
+                Ok(process)
+            }
+            false => {
+                let mut process = Command::new(executable);
+                process.args(&arguments[1..]);
+                Ok(process)
+            }
+        }
+    }
+}
+
+/// Resolve a placeholder such as `cmd:git rev-parse --short HEAD`, returning `None` if the
+/// placeholder is not a command
+pub(crate) fn resolve(placeholder: &str, base_dir: Option<&Path>, policy: &CommandPolicy) -> Option<Result<Value, Error>> {
+    let command = placeholder.strip_prefix(CMD_PREFIX)?.trim();
+    Some(run(command, base_dir, policy).map(Value::String))
+}
+
+fn run(command: &str, base_dir: Option<&Path>, policy: &CommandPolicy) -> Result<String, Error> {
+    let failed = |status: Option<i32>, message: String| Error::CommandFailed(command.to_string(), status, message);
+
+    let mut process = policy.command(command)?;
+    if let Some(base_dir) = base_dir {
+        process.current_dir(base_dir);
+    }
+
+    let mut child = process
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| failed(None, err.to_string()))?;
+
+    // Read the output while waiting, such that a command filling a pipe does not block
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    let deadline = Instant::now() + policy.timeout;
+    let status = loop {
+        match child.try_wait().map_err(|err| failed(None, err.to_string()))? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Error::CommandTimeout(command.to_string(), policy.timeout));
+            }
+            None => thread::sleep(Duration::from_millis(5))
+        }
+    };
+
+    let output = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+        let bytes = reader.and_then(|reader| reader.join().ok()).unwrap_or_default();
+        String::from_utf8_lossy(&bytes).trim().to_string()
+    };
+    let stdout = output(stdout);
+    let stderr = output(stderr);
+
+    match status.success() {
+        true => Ok(stdout),
+        false => Err(failed(status.code(), stderr))
+    }
+}
+
+fn read_to_end(mut reader: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = reader.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+/// Split the command into arguments at whitespace, where single or double quotes group words.
+/// Returns `None` if a quote is not terminated.
+fn split(command: &str) -> Option<Vec<String>> {
+    let mut arguments = Vec::new();
+    let mut argument = String::new();
+    let mut started = false;
+    let mut quote = None;
+
+    for char in command.chars() {
+        match (quote, char) {
+            (None, '"' | '\'') => {
+                quote = Some(char);
+                started = true;
+            }
+            (Some(open), _) if open == char => quote = None,
+            (None, char) if char.is_whitespace() => {
+                if started {
+                    arguments.push(std::mem::take(&mut argument));
+                    started = false;
+                }
+            }
+            (_, char) => {
+                argument.push(char);
+                started = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return None;
+    }
+    if started {
+        arguments.push(argument);
+    }
+    Some(arguments)
+}
@@ -3,7 +3,51 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Invalid regex pattern {0}")]
-    InvalidPattern(String),
+    InvalidPattern(String, #[source] regex::Error),
     #[error("Invalid json format")]
-    InvalidJson
+    InvalidJson,
+    #[error("Variable {0} called but never defined")]
+    UndefinedVariable(String),
+    #[error("Required variables {} are not defined", .0.join(", "))]
+    MissingVariables(Vec<String>),
+    #[error("Variable {0} has an invalid value: {1}")]
+    InvalidVariable(String, #[source] serde_json::Error),
+    #[error("Expression {0} is invalid: {1}")]
+    Expression(String, String),
+    #[error("Condition {0} does not result in a boolean")]
+    InvalidCondition(String),
+    #[error("Loop {0} is not valid")]
+    InvalidLoop(String),
+    #[error("Unable to read file {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+    #[error("File {0} is not valid JSON: {1}")]
+    InvalidFile(String, #[source] serde_json::Error),
+    #[error("Access to file {0} is denied: {1}")]
+    AccessDenied(String, String),
+    #[error("Command {0} is denied: {1}")]
+    CommandDenied(String, String),
+    #[error("Command {0} failed with exit status {status}: {2}", status = .1.map_or("none".to_string(), |status| status.to_string()))]
+    CommandFailed(String, Option<i32>, String),
+    #[error("Command {0} timed out after {1:?}")]
+    CommandTimeout(String, std::time::Duration),
+    #[error("Function {0} failed: {1}")]
+    Function(String, String),
+    #[error("Macro {0} is not defined")]
+    UndefinedMacro(String),
+    #[error("Macro signature {0} is not valid")]
+    InvalidMacro(String),
+    #[error("Macro {0} expects {1} arguments but got {2}")]
+    MacroArity(String, usize, usize),
+    #[error("Macro {0} calls itself recursively")]
+    RecursiveMacro(String),
+    #[error("Variable {0} is nested too deeply")]
+    NestingTooDeep(String),
+    #[error("Variable path {0} is not valid")]
+    InvalidPath(String),
+    #[error("Profile {0} is not defined")]
+    UnknownProfile(String),
+    #[error("Unable to (de)serialize json: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Validation failed: {0}")]
+    Validation(#[source] Box<dyn std::error::Error + Send + Sync>),
 }
@@ -0,0 +1,421 @@
+use serde_json::{Number, Value};
+
+use super::Error;
+
+/// Whether the placeholder is an expression such as `BASE_PORT + 1`, as opposed to the path of
+/// a variable. Operators and whitespace inside the selectors of a path are not taken into account.
+pub(crate) fn is_expression(text: &str) -> bool {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for char in text.chars() {
+        match (quote, char) {
+            (Some(open), _) if open == char => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if depth > 0 => quote = Some(char),
+            (None, '[') => depth += 1,
+            (None, ']') => depth = depth.saturating_sub(1),
+            (None, char) if depth == 0 && (char.is_whitespace() || "+-*/%()!=<>&|,\"'".contains(char)) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Evaluate the expression, where the values of variables are given by `lookup` and the results
+/// of macro calls such as `service('api', 8080)` by `call`
+pub(crate) fn evaluate(
+    text: &str,
+    lookup: impl Fn(&str) -> Result<Value, Error>,
+    call: impl Fn(&str, Vec<Value>) -> Result<Value, Error>
+) -> Result<Value, Error> {
+    let error = |message: String| Error::Expression(text.to_string(), message);
+
+    let tokens = tokenize(text).map_err(error)?;
+    let mut parser = Parser { tokens, position: 0 };
+    let expression = parser.or().map_err(error)?;
+    if let Some(token) = parser.tokens.get(parser.position) {
+        return Err(error(format!("unexpected {token:?}")));
+    }
+
+    let evaluator = Evaluator { text, lookup, call };
+    evaluator.evaluate(&expression)?.into_value().map_err(error)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Variable(String),
+    Bool(bool),
+    Null,
+    Operator(&'static str),
+    Open,
+    Close,
+    Comma
+}
+
+const OPERATORS: [&str; 17] = ["==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "(", ")", ","];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+
+    while let Some(char) = rest.chars().next() {
+        if let Some(operator) = OPERATORS.iter().find(|operator| rest.starts_with(**operator)) {
+            tokens.push(match *operator {
+                "(" => Token::Open,
+                ")" => Token::Close,
+                "," => Token::Comma,
+                operator => Token::Operator(operator)
+            });
+            rest = &rest[operator.len()..];
+        }
+        else if char == '"' || char == '\'' {
+            let end = rest[1..].find(char).ok_or_else(|| format!("unterminated string {rest}"))?;
+            tokens.push(Token::String(rest[1..end + 1].to_string()));
+            rest = &rest[end + 2..];
+        }
+        else if char.is_ascii_digit() {
+            let end = rest.find(|char: char| !char.is_ascii_digit() && char != '.').unwrap_or(rest.len());
+            let number = &rest[..end];
+            tokens.push(match number.contains('.') {
+                true => Token::Float(number.parse().map_err(|_| format!("invalid number {number}"))?),
+                false => Token::Integer(number.parse().map_err(|_| format!("invalid number {number}"))?)
+            });
+            rest = &rest[end..];
+        }
+        else if char.is_alphanumeric() || char == '_' || char == '$' {
+            let end = variable_end(rest)?;
+            tokens.push(match &rest[..end] {
+                "true" => Token::Bool(true),
+                "false" => Token::Bool(false),
+                "null" => Token::Null,
+                variable => Token::Variable(variable.to_string())
+            });
+            rest = &rest[end..];
+        }
+        else {
+            return Err(format!("unexpected character {char}"));
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// Find the end of a variable path, including its selectors
+fn variable_end(text: &str) -> Result<usize, String> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, char) in text.char_indices() {
+        match (quote, char) {
+            (Some(open), _) if open == char => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if depth > 0 => quote = Some(char),
+            (None, '[') => depth += 1,
+            (None, ']') if depth > 0 => depth -= 1,
+            (None, char) if depth == 0 && !(char.is_alphanumeric() || "_.$".contains(char)) => return Ok(index),
+            _ => {}
+        }
+    }
+
+    match depth {
+        0 => Ok(text.len()),
+        _ => Err(format!("unterminated selector in {text}"))
+    }
+}
+
+#[derive(Debug)]
+enum Expression {
+    Literal(Operand),
+    Variable(String),
+    Call(String, Vec<Expression>),
+    Unary(&'static str, Box<Expression>),
+    Binary(&'static str, Box<Expression>, Box<Expression>)
+}
+
+/// Recursive descent parser, from the lowest to the highest precedence
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize
+}
+
+impl Parser {
+    fn next_operator(&mut self, operators: &[&str]) -> Option<&'static str> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) if operators.contains(operator) => {
+                self.position += 1;
+                Some(operator)
+            }
+            _ => None
+        }
+    }
+
+    fn binary(&mut self, operators: &[&str], operand: fn(&mut Self) -> Result<Expression, String>) -> Result<Expression, String> {
+        let mut left = operand(self)?;
+        while let Some(operator) = self.next_operator(operators) {
+            left = Expression::Binary(operator, Box::new(left), Box::new(operand(self)?));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expression, String> {
+        self.binary(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expression, String> {
+        self.binary(&["&&"], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        let left = self.sum()?;
+        match self.next_operator(&["==", "!=", "<", "<=", ">", ">="]) {
+            Some(operator) => Ok(Expression::Binary(operator, Box::new(left), Box::new(self.sum()?))),
+            None => Ok(left)
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        self.binary(&["+", "-"], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        self.binary(&["*", "/", "%"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        match self.next_operator(&["!", "-"]) {
+            Some(operator) => Ok(Expression::Unary(operator, Box::new(self.unary()?))),
+            None => self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        let token = self.tokens.get(self.position).cloned().ok_or("unexpected end of expression")?;
+        self.position += 1;
+
+        let expression = match token {
+            Token::Integer(integer) => Expression::Literal(Operand::Integer(integer)),
+            Token::Float(float) => Expression::Literal(Operand::Float(float)),
+            Token::String(string) => Expression::Literal(Operand::Value(Value::String(string))),
+            Token::Bool(bool) => Expression::Literal(Operand::Value(Value::Bool(bool))),
+            Token::Null => Expression::Literal(Operand::Value(Value::Null)),
+            Token::Variable(name) if self.tokens.get(self.position) == Some(&Token::Open) => {
+                self.position += 1;
+                Expression::Call(name, self.arguments()?)
+            }
+            Token::Variable(variable) => Expression::Variable(variable),
+            Token::Open => {
+                let expression = self.or()?;
+                if self.tokens.get(self.position) != Some(&Token::Close) {
+                    return Err("missing closing parenthesis".to_string());
+                }
+                self.position += 1;
+                expression
+            }
+            token => return Err(format!("unexpected {token:?}"))
+        };
+
+        Ok(expression)
+    }
+
+    /// Parse the arguments of a call up to and including the closing parenthesis
+    fn arguments(&mut self) -> Result<Vec<Expression>, String> {
+        let mut arguments = Vec::new();
+        if self.tokens.get(self.position) == Some(&Token::Close) {
+            self.position += 1;
+            return Ok(arguments);
+        }
+
+        loop {
+            arguments.push(self.or()?);
+            match self.tokens.get(self.position) {
+                Some(Token::Comma) => self.position += 1,
+                Some(Token::Close) => {
+                    self.position += 1;
+                    return Ok(arguments);
+                }
+                _ => return Err("missing closing parenthesis".to_string())
+            }
+        }
+    }
+}
+
+/// Value during evaluation, where numbers are either integers or floats
+#[derive(Debug, Clone)]
+enum Operand {
+    Integer(i64),
+    Float(f64),
+    Value(Value)
+}
+
+impl Operand {
+    fn from_value(value: Value) -> Operand {
+        match &value {
+            Value::Number(number) => match number.as_i64() {
+                Some(integer) => Operand::Integer(integer),
+                None => Operand::Float(number.as_f64().unwrap_or(f64::NAN))
+            },
+            _ => Operand::Value(value)
+        }
+    }
+
+    fn into_value(self) -> Result<Value, String> {
+        match self {
+            Operand::Integer(integer) => Ok(Value::from(integer)),
+            Operand::Float(float) => Number::from_f64(float).map(Value::Number).ok_or_else(|| format!("{float} is not a valid number")),
+            Operand::Value(value) => Ok(value)
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Operand::Integer(integer) => Some(*integer as f64),
+            Operand::Float(float) => Some(*float),
+            Operand::Value(_) => None
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Operand::Value(Value::Bool(bool)) => Some(*bool),
+            _ => None
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Operand::Integer(_) => "integer",
+            Operand::Float(_) => "float",
+            Operand::Value(Value::Null) => "null",
+            Operand::Value(Value::Bool(_)) => "boolean",
+            Operand::Value(Value::Number(_)) => "number",
+            Operand::Value(Value::String(_)) => "string",
+            Operand::Value(Value::Array(_)) => "array",
+            Operand::Value(Value::Object(_)) => "object"
+        }
+    }
+}
+
+struct Evaluator<'a, L, C> {
+    text: &'a str,
+    lookup: L,
+    call: C
+}
+
+impl<L, C> Evaluator<'_, L, C>
+where
+    L: Fn(&str) -> Result<Value, Error>,
+    C: Fn(&str, Vec<Value>) -> Result<Value, Error>
+{
+    fn error(&self, message: String) -> Error {
+        Error::Expression(self.text.to_string(), message)
+    }
+
+    fn evaluate(&self, expression: &Expression) -> Result<Operand, Error> {
+        match expression {
+            Expression::Literal(operand) => Ok(operand.clone()),
+            Expression::Variable(variable) => Ok(Operand::from_value((self.lookup)(variable)?)),
+            Expression::Call(name, arguments) => {
+                let arguments = arguments.iter()
+                    .map(|argument| self.evaluate(argument)?.into_value().map_err(|message| self.error(message)))
+                    .collect::<Result<Vec<Value>, Error>>()?;
+                Ok(Operand::from_value((self.call)(name, arguments)?))
+            }
+            Expression::Unary(operator, operand) => {
+                let operand = self.evaluate(operand)?;
+                match (*operator, &operand) {
+                    ("!", _) if let Some(bool) = operand.as_bool() => Ok(Operand::Value(Value::Bool(!bool))),
+                    ("-", Operand::Integer(integer)) => integer.checked_neg().map(Operand::Integer).ok_or_else(|| self.error("integer overflow".to_string())),
+                    ("-", Operand::Float(float)) => Ok(Operand::Float(-float)),
+                    (operator, operand) => Err(self.error(format!("cannot apply {operator} to {}", operand.type_name())))
+                }
+            }
+            Expression::Binary(operator @ ("&&" | "||"), left, right) => {
+                let left = self.boolean(operator, self.evaluate(left)?)?;
+                // Short circuit the right side
+                match (*operator, left) {
+                    ("&&", false) => Ok(Operand::Value(Value::Bool(false))),
+                    ("||", true) => Ok(Operand::Value(Value::Bool(true))),
+                    _ => Ok(Operand::Value(Value::Bool(self.boolean(operator, self.evaluate(right)?)?)))
+                }
+            }
+            Expression::Binary(operator, left, right) => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                self.binary(operator, left, right)
+            }
+        }
+    }
+
+    fn boolean(&self, operator: &str, operand: Operand) -> Result<bool, Error> {
+        operand.as_bool().ok_or_else(|| self.error(format!("cannot apply {operator} to {}", operand.type_name())))
+    }
+
+    fn binary(&self, operator: &str, left: Operand, right: Operand) -> Result<Operand, Error> {
+        let mismatch = |left: &Operand, right: &Operand| {
+            self.error(format!("cannot apply {operator} to {} and {}", left.type_name(), right.type_name()))
+        };
+        let bool = |bool: bool| Ok(Operand::Value(Value::Bool(bool)));
+
+        match (operator, &left, &right) {
+            ("==", _, _) => bool(equals(&left, &right)),
+            ("!=", _, _) => bool(!equals(&left, &right)),
+            ("+", Operand::Value(Value::String(left)), Operand::Value(Value::String(right))) => {
+                Ok(Operand::Value(Value::String(format!("{left}{right}"))))
+            }
+            ("<" | "<=" | ">" | ">=", Operand::Value(Value::String(left)), Operand::Value(Value::String(right))) => {
+                bool(compare(operator, left.cmp(right)))
+            }
+            ("<" | "<=" | ">" | ">=", _, _) => match (left.as_float(), right.as_float()) {
+                (Some(left), Some(right)) => match left.partial_cmp(&right) {
+                    Some(ordering) => bool(compare(operator, ordering)),
+                    None => bool(false)
+                },
+                _ => Err(mismatch(&left, &right))
+            },
+            (_, Operand::Integer(left), Operand::Integer(right)) => {
+                let result = match operator {
+                    "+" => left.checked_add(*right),
+                    "-" => left.checked_sub(*right),
+                    "*" => left.checked_mul(*right),
+                    "/" | "%" if *right == 0 => return Err(self.error("division by zero".to_string())),
+                    "/" => left.checked_div(*right),
+                    "%" => left.checked_rem(*right),
+                    _ => return Err(mismatch(&Operand::Integer(*left), &Operand::Integer(*right)))
+                };
+                result.map(Operand::Integer).ok_or_else(|| self.error("integer overflow".to_string()))
+            }
+            _ => match (left.as_float(), right.as_float()) {
+                (Some(left), Some(right)) => match operator {
+                    "+" => Ok(Operand::Float(left + right)),
+                    "-" => Ok(Operand::Float(left - right)),
+                    "*" => Ok(Operand::Float(left * right)),
+                    "/" | "%" if right == 0.0 => Err(self.error("division by zero".to_string())),
+                    "/" => Ok(Operand::Float(left / right)),
+                    "%" => Ok(Operand::Float(left % right)),
+                    _ => Err(mismatch(&Operand::Float(left), &Operand::Float(right)))
+                },
+                _ => Err(mismatch(&left, &right))
+            }
+        }
+    }
+}
+
+fn equals(left: &Operand, right: &Operand) -> bool {
+    match (left.as_float(), right.as_float(), left, right) {
+        (Some(left), Some(right), _, _) => left == right,
+        (_, _, Operand::Value(left), Operand::Value(right)) => left == right,
+        _ => false
+    }
+}
+
+fn compare(operator: &str, ordering: std::cmp::Ordering) -> bool {
+    match operator {
+        "<" => ordering.is_lt(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        _ => ordering.is_ge()
+    }
+}
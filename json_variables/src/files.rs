@@ -0,0 +1,186 @@
+use std::{fs::File, io::Read, path::{Path, PathBuf}};
+
+use serde_json::Value;
+
+use super::Error;
+
+/// Prefix of a placeholder which inlines the content of a file as a string
+pub const FILE_PREFIX: &str = "file:";
+
+/// Prefix of a placeholder which inlines a JSON file, optionally at a JSON pointer after `#`
+pub const JSON_PREFIX: &str = "json:";
+
+/// The files which can be read by `${file:...}` and `${json:...}` placeholders.
+///
+/// By default only the files inside [`Variables::base_dir`](super::Variables::base_dir), or the
+/// current directory if it is not set, can be read, which prevents templates from reading files
+/// such as `/etc/shadow` or escaping the configuration directory through `..`. Other directories
+/// are allowed through [`Self::allow_root`], and [`Self::unrestricted`] allows every file.
+/// Symlinks are resolved before the path is checked against the allowed directories. A
+/// violation of the policy fails with [`Error::AccessDenied`].
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use json_variables::{Error, FileAccessPolicy};
+///
+/// let mut variables = json_variables::from_json(json!({})).unwrap();
+/// variables.file_access = FileAccessPolicy::default()
+///     .allow_root("./config")
+///     .follow_symlinks(false)
+///     .max_size(1024 * 1024);
+///
+/// let result = variables.apply(json!("${file:/etc/shadow}"));
+/// assert!(matches!(result, Err(Error::AccessDenied(..))));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileAccessPolicy {
+    enabled: bool,
+    unrestricted: bool,
+    roots: Vec<PathBuf>,
+    follow_symlinks: bool,
+    max_size: Option<u64>
+}
+
+impl Default for FileAccessPolicy {
+    fn default() -> Self {
+        FileAccessPolicy { enabled: true, unrestricted: false, roots: Vec::new(), follow_symlinks: true, max_size: None }
+    }
+}
+
+impl FileAccessPolicy {
+    /// Deny the access to every file
+    pub fn disabled() -> Self {
+        FileAccessPolicy { enabled: false, ..Default::default() }
+    }
+
+    /// Allow the access to every file, unless directories are allowed through
+    /// [`Self::allow_root`]
+    pub fn unrestricted() -> Self {
+        FileAccessPolicy { unrestricted: true, ..Default::default() }
+    }
+
+    /// Only allow files inside the given directory, or any of the other allowed directories,
+    /// instead of the base directory. A relative directory is resolved against
+    /// [`Variables::base_dir`](super::Variables::base_dir), or the current directory if it is
+    /// not set.
+    pub fn allow_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    /// Whether the path given by the template may pass through symlinks, which is allowed by
+    /// default. The target of a symlink still has to be inside an allowed directory.
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Deny files larger than the given number of bytes
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Open the file of the template after checking the policy, where `path` has been joined with
+    /// the base directory into `full_path`. The file is opened once through its canonical path,
+    /// such that the checked file is the file which is read. Only regular files are opened, as
+    /// opening a FIFO blocks until it is written to.
+    fn open(&self, path: &str, full_path: &Path, base_dir: Option<&Path>) -> Result<File, Error> {
+        let denied = |reason: &str| Error::AccessDenied(path.to_string(), reason.to_string());
+        if !self.enabled {
+            return Err(denied("file access is disabled"));
+        }
+
+        if !self.follow_symlinks {
+            let mut current = base_dir.map(Path::to_path_buf).unwrap_or_default();
+            for component in Path::new(path).components() {
+                current.push(component);
+                if current.symlink_metadata().is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+                    return Err(denied("symlinks are not followed"));
+                }
+            }
+        }
+
+        let canonical = full_path.canonicalize().map_err(|err| Error::Io(path.to_string(), err))?;
+        let base = [PathBuf::from(".")];
+        let roots = match self.roots.is_empty() {
+            true if self.unrestricted => &[][..],
+            true => &base[..],
+            false => &self.roots[..]
+        };
+        if !roots.is_empty() && !roots.iter()
+            .filter_map(|root| match base_dir {
+                Some(base_dir) => base_dir.join(root).canonicalize().ok(),
+                None => root.canonicalize().ok()
+            })
+            .any(|root| canonical.starts_with(root))
+        {
+            return Err(denied("the file is outside of the allowed directories"));
+        }
+
+        let metadata = canonical.metadata().map_err(|err| Error::Io(path.to_string(), err))?;
+        if !metadata.is_file() {
+            return Err(denied("the path is not a regular file"));
+        }
+
+        // The path may have been replaced after it was checked, so the opened file is checked again
+        let file = File::open(&canonical).map_err(|err| Error::Io(path.to_string(), err))?;
+        let metadata = file.metadata().map_err(|err| Error::Io(path.to_string(), err))?;
+        if !metadata.is_file() {
+            return Err(denied("the path is not a regular file"));
+        }
+        if let Some(max_size) = self.max_size.filter(|max_size| metadata.len() > *max_size) {
+            return Err(self.too_large(path, max_size));
+        }
+
+        Ok(file)
+    }
+
+    fn too_large(&self, path: &str, max_size: u64) -> Error {
+        Error::AccessDenied(path.to_string(), format!("the file is larger than {max_size} bytes"))
+    }
+}
+
+/// Resolve a placeholder such as `file:./certs/ca.pem` or `json:./regions.json#/eu`, returning
+/// `None` if the placeholder does not refer to a file
+pub(crate) fn resolve(placeholder: &str, base_dir: Option<&Path>, policy: &FileAccessPolicy) -> Option<Result<Value, Error>> {
+    if let Some(path) = placeholder.strip_prefix(FILE_PREFIX) {
+        return Some(read(path.trim(), base_dir, policy).map(Value::String));
+    }
+
+    let reference = placeholder.strip_prefix(JSON_PREFIX)?.trim();
+    let (path, pointer) = match reference.split_once('#') {
+        Some((path, pointer)) => (path, Some(pointer)),
+        None => (reference, None)
+    };
+
+    Some(read(path, base_dir, policy).and_then(|content| {
+        let value: Value = serde_json::from_str(&content).map_err(|err| Error::InvalidFile(path.to_string(), err))?;
+        match pointer {
+            Some(pointer) => value.pointer(pointer)
+                .cloned()
+                .ok_or_else(|| Error::UndefinedVariable(placeholder.to_string())),
+            None => Ok(value)
+        }
+    }))
+}
+
+fn read(path: &str, base_dir: Option<&Path>, policy: &FileAccessPolicy) -> Result<String, Error> {
+    let full_path = match base_dir {
+        Some(base_dir) => base_dir.join(path),
+        None => PathBuf::from(path)
+    };
+
+    // The file may grow after its size is checked, so the size is limited while reading as well
+    let file = policy.open(path, &full_path, base_dir)?;
+    let mut content = String::new();
+    file.take(policy.max_size.map_or(u64::MAX, |max_size| max_size.saturating_add(1)))
+        .read_to_string(&mut content)
+        .map_err(|err| Error::Io(path.to_string(), err))?;
+
+    match policy.max_size {
+        Some(max_size) if content.len() as u64 > max_size => Err(policy.too_large(path, max_size)),
+        _ => Ok(content)
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt,
+    hash::{BuildHasher, Hasher},
+    panic::RefUnwindSafe,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use chrono::{format::{Item, StrftimeItems}, DateTime, Utc};
+use serde_json::Value;
+
+use super::Error;
+
+/// The built-in functions which can be called from placeholders, such as `${now('%Y-%m-%d')}`,
+/// `${uuid()}`, `${random_int(1, 100)}` and `${hostname()}`.
+///
+/// The clock, the seed of the random numbers and the host name can be injected, such that tests
+/// and reproducible builds produce stable output. With a seed, every render, such as a call to
+/// [`Variables::apply`](super::Variables::apply) or `parse`, starts from the same state. A clone
+/// has a state of its own, such that rendering a clone does not affect the original.
+///
+/// # Examples
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use serde_json::json;
+/// use json_variables::Functions;
+///
+/// let mut variables = json_variables::from_json(json!({})).unwrap();
+/// variables.functions = Functions::default()
+///     .with_fixed_time(Utc.with_ymd_and_hms(2024, 1, 31, 12, 0, 0).unwrap())
+///     .with_seed(42);
+///
+/// let value = variables.apply(json!("build-${now('%Y%m%d')}")).unwrap();
+/// assert_eq!(value, json!("build-20240131"));
+/// ```
+pub struct Functions {
+    clock: Arc<dyn Fn() -> DateTime<Utc> + Send + Sync + RefUnwindSafe>,
+    hostname: Option<String>,
+    seed: Option<u64>,
+    memoize: bool,
+    state: Arc<Mutex<State>>
+}
+
+/// State shared with the scopes of loops, macros and nested blocks during a render
+struct State {
+    random: SplitMix64,
+    memoized: HashMap<String, Value>
+}
+
+impl State {
+    fn new(seed: Option<u64>) -> Arc<Mutex<State>> {
+        Arc::new(Mutex::new(State { random: SplitMix64(seed.unwrap_or_else(entropy)), memoized: HashMap::new() }))
+    }
+}
+
+impl Default for Functions {
+    fn default() -> Self {
+        Functions {
+            clock: Arc::new(Utc::now),
+            hostname: None,
+            seed: None,
+            memoize: false,
+            state: State::new(None)
+        }
+    }
+}
+
+impl Clone for Functions {
+    fn clone(&self) -> Self {
+        Functions { state: State::new(self.seed), ..self.share() }
+    }
+}
+
+impl fmt::Debug for Functions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Functions")
+            .field("hostname", &self.hostname)
+            .field("seed", &self.seed)
+            .field("memoize", &self.memoize)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Functions {
+    /// Use the given clock for `now`
+    pub fn with_clock(mut self, clock: impl Fn() -> DateTime<Utc> + Send + Sync + RefUnwindSafe + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Let `now` always return the given time
+    pub fn with_fixed_time(self, time: DateTime<Utc>) -> Self {
+        self.with_clock(move || time)
+    }
+
+    /// Generate the values of `uuid` and `random_int` from the given seed, restarting at every
+    /// render
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self.state = State::new(Some(seed));
+        self
+    }
+
+    /// Let `hostname` return the given name instead of the name of the machine
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+
+    /// Evaluate every call with the same arguments only once per render, such that for example
+    /// every `${uuid()}` of a render results in the same identifier. By default, every call site
+    /// is evaluated independently.
+    pub fn memoize(mut self, memoize: bool) -> Self {
+        self.memoize = memoize;
+        self
+    }
+
+    /// Copy of the functions which shares the state, used by the scopes of a single render
+    pub(crate) fn share(&self) -> Functions {
+        Functions {
+            clock: self.clock.clone(),
+            hostname: self.hostname.clone(),
+            seed: self.seed,
+            memoize: self.memoize,
+            state: self.state.clone()
+        }
+    }
+
+    /// Start a new render, clearing the memoized values and restarting from the seed
+    pub(crate) fn begin_render(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.memoized.clear();
+        if let Some(seed) = self.seed {
+            state.random = SplitMix64(seed);
+        }
+    }
+
+    /// Call the built-in function, returning `None` if there is no function with the name
+    pub(crate) fn call(&self, name: &str, arguments: &[Value]) -> Option<Result<Value, Error>> {
+        if !matches!(name, "now" | "uuid" | "random_int" | "hostname") {
+            return None;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let key = format!("{name}{}", Value::from(arguments.to_vec()));
+        if self.memoize && let Some(value) = state.memoized.get(&key) {
+            return Some(Ok(value.clone()));
+        }
+
+        let error = |message: String| Error::Function(name.to_string(), message);
+        let value = match (name, arguments) {
+            ("now", []) => Ok(Value::String((self.clock)().to_rfc3339())),
+            ("now", [Value::String(format)]) => {
+                let items: Vec<Item> = StrftimeItems::new(format).collect();
+                match items.contains(&Item::Error) {
+                    true => Err(error(format!("invalid format {format}"))),
+                    false => Ok(Value::String((self.clock)().format_with_items(items.into_iter()).to_string()))
+                }
+            }
+            ("uuid", []) => Ok(Value::String(uuid(&mut state.random))),
+            ("random_int", [min, max]) => match (min.as_i64(), max.as_i64()) {
+                (Some(min), Some(max)) if min <= max => {
+                    let range = (max as i128 - min as i128 + 1) as u128;
+                    Ok(Value::from((min as i128 + (state.random.next() as u128 % range) as i128) as i64))
+                }
+                _ => Err(error(format!("invalid range {min} to {max}")))
+            },
+            ("hostname", []) => Ok(Value::String(
+                self.hostname.clone().unwrap_or_else(|| gethostname::gethostname().to_string_lossy().into_owned())
+            )),
+            (_, arguments) => Err(error(format!("invalid arguments {}", Value::from(arguments.to_vec()))))
+        };
+
+        if self.memoize && let Ok(value) = &value {
+            state.memoized.insert(key, value.clone());
+        }
+        Some(value)
+    }
+}
+
+/// Random version 4 UUID
+fn uuid(random: &mut SplitMix64) -> String {
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&random.next().to_be_bytes());
+    bytes[8..].copy_from_slice(&random.next().to_be_bytes());
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+/// Seed of the random numbers when none is given
+fn entropy() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos()).unwrap_or_default());
+    hasher.finish()
+}
+
+/// Small pseudo random number generator, which is good enough for identifiers and test data
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
@@ -107,7 +107,8 @@
 //! ```
 //!
 //! By default, the matching pattern is ${\<variable\>}. This pattern also allows for value
-//! accessing or array indexing through the `.` accessor.
+//! accessing or array indexing through the `.` accessor, as well as the
+//! selectors `[*]`, `[start:end]` and `[?key=="value"]` which result in an array.
 //!
 //! ```json
 //! {
@@ -143,4 +144,75 @@ pub use variables::*;
 pub mod error;
 pub use error::*;
 
-pub use derive_json_variables::*;
\ No newline at end of file
+pub mod templated;
+pub use templated::*;
+
+pub mod merge;
+pub use merge::*;
+
+pub mod stack;
+pub use stack::*;
+
+pub mod builder;
+pub use builder::*;
+
+pub mod functions;
+pub use functions::*;
+
+pub mod files;
+pub use files::*;
+
+pub mod commands;
+pub use commands::*;
+
+mod coerce;
+
+mod expression;
+
+mod path;
+
+#[cfg(feature = "schemars")]
+pub mod schema;
+
+pub use derive_json_variables::*;
+
+#[doc(hidden)]
+pub mod __private {
+    //! Re-exports used by the `JsonVariables` derive so that the generated code does not depend
+    //! on the crates imported at the call site
+    pub use serde;
+    pub use serde_json;
+    #[cfg(feature = "schemars")]
+    pub use schemars;
+
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+
+    use super::{Error, Variables};
+
+    /// Replace the variables in the value, marking strings produced by placeholders if the
+    /// variables coerce
+    pub fn apply(variables: &Variables, value: Value) -> Result<Value, Error> {
+        variables.apply_value(value, variables.coerce)
+    }
+
+    /// Compile the pattern given to the `pattern` attribute of the derive
+    pub fn pattern(pattern: &str) -> Result<regex::Regex, Error> {
+        regex::Regex::new(pattern).map_err(|err| Error::InvalidPattern(pattern.to_string(), err))
+    }
+
+    /// Copy of the variables for the scope of a nested field, which is part of the same render
+    pub fn scope(variables: &Variables) -> Variables {
+        variables.share()
+    }
+
+    /// Start a new render of the built-in functions
+    pub fn begin_render(variables: &Variables) {
+        variables.functions.begin_render();
+    }
+
+    /// Deserialize a value returned by [`apply`]
+    pub fn from_value<T: DeserializeOwned>(variables: &Variables, value: Value) -> Result<T, Error> {
+        variables.deserialize_applied(value)
+    }
+}
@@ -0,0 +1,67 @@
+use serde_json::{Map, Value};
+
+/// How the variables of a layer are combined with the variables below it. Variables are always
+/// combined per top level name, the strategy decides what happens when both layers define the
+/// same variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeStrategy {
+    pub objects: ObjectMerge,
+    pub arrays: ArrayMerge
+}
+
+/// How two objects defined by the same variable are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ObjectMerge {
+    /// The object of the upper layer replaces the object below it as a whole
+    #[default]
+    Replace,
+    /// The objects are merged key by key, recursively
+    Deep
+}
+
+/// How two arrays defined by the same variable are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// The array of the upper layer replaces the array below it
+    #[default]
+    Replace,
+    /// The elements of the upper layer are appended to the array below it
+    Append
+}
+
+impl MergeStrategy {
+    /// Replace variables defined by both layers as a whole
+    pub const REPLACE: MergeStrategy = MergeStrategy { objects: ObjectMerge::Replace, arrays: ArrayMerge::Replace };
+
+    /// Merge objects key by key and replace any other value
+    pub const DEEP: MergeStrategy = MergeStrategy { objects: ObjectMerge::Deep, arrays: ArrayMerge::Replace };
+
+    /// Use the given array merge with this strategy
+    pub fn arrays(self, arrays: ArrayMerge) -> MergeStrategy {
+        MergeStrategy { arrays, ..self }
+    }
+}
+
+/// Merge the overlay into the base variables following the strategy
+pub(crate) fn merge_map(base: &mut Map<String, Value>, overlay: Map<String, Value>, strategy: MergeStrategy) {
+    for (key, value) in overlay {
+        match base.get_mut(&key) {
+            Some(base) => merge(base, value, strategy),
+            None => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn merge(base: &mut Value, overlay: Value, strategy: MergeStrategy) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) if strategy.objects == ObjectMerge::Deep => {
+            merge_map(base, overlay, strategy)
+        }
+        (Value::Array(base), Value::Array(overlay)) if strategy.arrays == ArrayMerge::Append => {
+            base.extend(overlay)
+        }
+        (base, overlay) => *base = overlay
+    }
+}
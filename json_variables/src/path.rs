@@ -0,0 +1,227 @@
+use serde_json::{Map, Value};
+
+use super::Error;
+
+/// Segment of a variable path such as `SERVERS[*].host`, `LIST[1:3]` or
+/// `USERS[?role=="admin"].name`
+#[derive(Debug, PartialEq)]
+enum Segment {
+    /// `.key` or `[key]`, which also indexes an array if the key is a number
+    Key(String),
+    /// `[1]`
+    Index(usize),
+    /// `[*]`, every element of an array or every value of an object
+    Wildcard,
+    /// `[start:end]`, where negative bounds count from the end of the array
+    Slice(Option<i64>, Option<i64>),
+    /// `[?key=="value"]`, the elements of an array which match the predicate
+    Filter(Filter)
+}
+
+#[derive(Debug, PartialEq)]
+struct Filter {
+    path: Vec<String>,
+    equal: bool,
+    value: Value
+}
+
+/// Whether the path uses the selectors `[...]`, as opposed to a plain path in the format
+/// var.var.var
+pub(crate) fn is_selector(path: &str) -> bool {
+    path.contains('[')
+}
+
+/// Select the value of the path in the variables. A path with a wildcard, slice or filter
+/// selects an array of all matching values.
+///
+/// Returns [`Error::InvalidPath`] if the path is not valid and `Ok(None)` if no value is
+/// defined at the path.
+pub(crate) fn select(variables: &Map<String, Value>, path: &str) -> Result<Option<Value>, Error> {
+    select_scoped(&Map::new(), variables, path)
+}
+
+/// Select the value of the path as done by [`select`], where the local variables shadow the
+/// variables with the same top level name
+pub(crate) fn select_scoped(locals: &Map<String, Value>, variables: &Map<String, Value>, path: &str) -> Result<Option<Value>, Error> {
+    let segments = parse(path).map_err(|_| Error::InvalidPath(path.to_string()))?;
+    let mut segments = segments.into_iter();
+    let Some(Segment::Key(first)) = segments.next() else {
+        return Err(Error::InvalidPath(path.to_string()));
+    };
+
+    let Some(value) = locals.get(&first).or_else(|| variables.get(&first)) else {
+        return Ok(None);
+    };
+
+    let mut selection = Selection::Single(value);
+    for segment in segments {
+        selection = match selection {
+            Selection::Single(value) => match select_segment(value, &segment) {
+                Some(Selection::Single(value)) => Selection::Single(value),
+                Some(Selection::Many(values)) => Selection::Many(values),
+                None => return Ok(None)
+            },
+            Selection::Many(values) => Selection::Many(
+                values.into_iter()
+                    .filter_map(|value| select_segment(value, &segment))
+                    .flat_map(Selection::into_values)
+                    .collect()
+            )
+        };
+    }
+
+    Ok(Some(match selection {
+        Selection::Single(value) => value.clone(),
+        Selection::Many(values) => Value::Array(values.into_iter().cloned().collect())
+    }))
+}
+
+enum Selection<'a> {
+    Single(&'a Value),
+    Many(Vec<&'a Value>)
+}
+
+impl<'a> Selection<'a> {
+    fn into_values(self) -> Vec<&'a Value> {
+        match self {
+            Selection::Single(value) => vec![value],
+            Selection::Many(values) => values
+        }
+    }
+}
+
+fn select_segment<'a>(value: &'a Value, segment: &Segment) -> Option<Selection<'a>> {
+    let selection = match (segment, value) {
+        (Segment::Key(key), Value::Object(map)) => Selection::Single(map.get(key)?),
+        (Segment::Key(key), Value::Array(array)) => Selection::Single(array.get(key.parse::<usize>().ok()?)?),
+        (Segment::Index(index), Value::Array(array)) => Selection::Single(array.get(*index)?),
+        (Segment::Wildcard, Value::Array(array)) => Selection::Many(array.iter().collect()),
+        (Segment::Wildcard, Value::Object(map)) => Selection::Many(map.values().collect()),
+        (Segment::Slice(start, end), Value::Array(array)) => {
+            let bound = |bound: i64| match bound < 0 {
+                true => array.len().saturating_sub(bound.unsigned_abs() as usize),
+                false => (bound as usize).min(array.len())
+            };
+            let start = start.map_or(0, bound);
+            let end = end.map_or(array.len(), bound);
+            Selection::Many(array.get(start..end.max(start)).unwrap_or_default().iter().collect())
+        }
+        (Segment::Filter(filter), Value::Array(array)) => Selection::Many(array.iter().filter(|value| filter.matches(value)).collect()),
+        (Segment::Filter(filter), Value::Object(map)) => Selection::Many(map.values().filter(|value| filter.matches(value)).collect()),
+        _ => return None
+    };
+
+    Some(selection)
+}
+
+impl Filter {
+    fn matches(&self, value: &Value) -> bool {
+        let value = self.path.iter().try_fold(value, |value, key| match value {
+            Value::Object(map) => map.get(key),
+            Value::Array(array) => array.get(key.parse::<usize>().ok()?),
+            _ => None
+        });
+
+        match value {
+            Some(value) => (*value == self.value) == self.equal,
+            None => !self.equal
+        }
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Segment>, ()> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    let mut first = true;
+
+    while !rest.is_empty() {
+        if let Some(selector) = rest.strip_prefix('[') {
+            let end = selector_end(selector).ok_or(())?;
+            segments.push(parse_selector(&selector[..end])?);
+            rest = &selector[end + 1..];
+            continue;
+        }
+
+        let key = match first {
+            true => rest,
+            false => rest.strip_prefix('.').ok_or(())?
+        };
+        let end = key.find(['.', '[']).unwrap_or(key.len());
+        if end == 0 {
+            return Err(());
+        }
+        segments.push(Segment::Key(key[..end].to_string()));
+        rest = &key[end..];
+        first = false;
+    }
+
+    Ok(segments)
+}
+
+/// Find the closing bracket of a selector, skipping brackets inside quoted strings
+fn selector_end(selector: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, char) in selector.char_indices() {
+        match (quote, char) {
+            (None, '"' | '\'') => quote = Some(char),
+            (Some(open), _) if open == char => quote = None,
+            (None, ']') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_selector(selector: &str) -> Result<Segment, ()> {
+    let selector = selector.trim();
+    if selector == "*" {
+        return Ok(Segment::Wildcard);
+    }
+
+    if let Some(filter) = selector.strip_prefix('?') {
+        return parse_filter(filter).map(Segment::Filter);
+    }
+
+    if let Some((start, end)) = selector.split_once(':') {
+        let bound = |bound: &str| match bound.trim() {
+            "" => Ok(None),
+            bound => bound.parse::<i64>().map(Some).map_err(|_| ())
+        };
+        return Ok(Segment::Slice(bound(start)?, bound(end)?));
+    }
+
+    if let Ok(index) = selector.parse::<usize>() {
+        return Ok(Segment::Index(index));
+    }
+
+    let key = ['"', '\'']
+        .into_iter()
+        .find_map(|quote| selector.strip_prefix(quote).and_then(|key| key.strip_suffix(quote)))
+        .unwrap_or(selector);
+    match key.is_empty() {
+        true => Err(()),
+        false => Ok(Segment::Key(key.to_string()))
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<Filter, ()> {
+    let (path, equal, value) = match (filter.split_once("=="), filter.split_once("!=")) {
+        (Some((path, value)), _) => (path, true, value),
+        (None, Some((path, value))) => (path, false, value),
+        (None, None) => return Err(())
+    };
+
+    let path = path.trim();
+    let path = path.strip_prefix("@.").unwrap_or(path);
+    if path.is_empty() || path.split('.').any(str::is_empty) {
+        return Err(());
+    }
+
+    let value = value.trim();
+    let value = match value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')) {
+        Some(value) => Value::String(value.to_string()),
+        None => serde_json::from_str(value).map_err(|_| ())?
+    };
+
+    Ok(Filter { path: path.split('.').map(str::to_string).collect(), equal, value })
+}
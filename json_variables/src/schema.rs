@@ -0,0 +1,126 @@
+use regex::Regex;
+use schemars::{json_schema, Schema, SchemaGenerator};
+use serde_json::{Map, Value};
+
+/// Keywords whose value is a single subschema which may be replaced by a placeholder
+const SUBSCHEMA_KEYWORDS: [&str; 3] = ["additionalProperties", "items", "unevaluatedProperties"];
+/// Keywords whose value is an object of subschemas which may be replaced by a placeholder
+const SUBSCHEMA_MAP_KEYWORDS: [&str; 2] = ["properties", "patternProperties"];
+/// Keywords whose value is an array of subschemas which may be replaced by a placeholder
+const SUBSCHEMA_ARRAY_KEYWORDS: [&str; 1] = ["prefixItems"];
+/// Keywords whose subschemas are alternatives of the same value, these are not wrapped themselves
+const COMBINATOR_KEYWORDS: [&str; 3] = ["anyOf", "oneOf", "allOf"];
+
+/// Schema of a string which consists of a single placeholder of the given pattern
+pub fn placeholder_schema(pattern: &Regex) -> Schema {
+    pattern_schema(pattern.as_str())
+}
+
+fn pattern_schema(pattern: &str) -> Schema {
+    let pattern = format!("^(?:{pattern})$");
+    json_schema!({
+        "type": "string",
+        "pattern": pattern
+    })
+}
+
+/// Create the schema of the templated form of a type, given the schema of its data. Every value
+/// inside the data may also be a placeholder string matching the `pattern`, such as
+/// [`default_pattern`](super::default_pattern) or the `pattern` attribute of the derive.
+///
+/// If `data_field_name` is `None`, the data is flattened next to the variables block. Definitions
+/// of the data schema are moved into the `generator`.
+pub fn templated_schema(
+    generator: &mut SchemaGenerator,
+    data: Schema,
+    variables_field_name: &str,
+    data_field_name: Option<&str>,
+    pattern: &str
+) -> Schema {
+    let placeholder = pattern_schema(pattern).to_value();
+
+    let mut data = data.to_value();
+    if let Value::Object(ref mut data) = data {
+        data.remove("$schema");
+        for key in ["$defs", "definitions"] {
+            if let Some(Value::Object(definitions)) = data.remove(key) {
+                for (name, mut definition) in definitions {
+                    allow_placeholders(&mut definition, &placeholder);
+                    generator.definitions_mut().insert(name, definition);
+                }
+            }
+        }
+    }
+    allow_placeholders(&mut data, &placeholder);
+
+    let variables = json_schema!({ "type": "object" }).to_value();
+
+    match data_field_name {
+        Some(data_field_name) => {
+            let mut properties = Map::new();
+            properties.insert(variables_field_name.to_string(), variables);
+            properties.insert(data_field_name.to_string(), with_placeholder(data, &placeholder));
+
+            json_schema!({
+                "type": "object",
+                "properties": properties,
+                "required": [data_field_name]
+            })
+        }
+        None => {
+            if let Value::Object(ref mut data) = data
+                && let Value::Object(properties) = data
+                    .entry("properties")
+                    .or_insert_with(|| Value::Object(Map::new())) {
+                properties.insert(variables_field_name.to_string(), variables);
+            }
+
+            Schema::try_from(data).unwrap_or_default()
+        }
+    }
+}
+
+/// Allow a placeholder in place of every subschema of the given schema
+fn allow_placeholders(schema: &mut Value, placeholder: &Value) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+
+    for keyword in SUBSCHEMA_KEYWORDS {
+        if let Some(subschema) = schema.get_mut(keyword) {
+            wrap(subschema, placeholder);
+        }
+    }
+    for keyword in SUBSCHEMA_MAP_KEYWORDS {
+        if let Some(Value::Object(subschemas)) = schema.get_mut(keyword) {
+            subschemas.values_mut().for_each(|subschema| wrap(subschema, placeholder));
+        }
+    }
+    for keyword in SUBSCHEMA_ARRAY_KEYWORDS {
+        if let Some(Value::Array(subschemas)) = schema.get_mut(keyword) {
+            subschemas.iter_mut().for_each(|subschema| wrap(subschema, placeholder));
+        }
+    }
+    for keyword in COMBINATOR_KEYWORDS {
+        if let Some(Value::Array(subschemas)) = schema.get_mut(keyword) {
+            subschemas.iter_mut().for_each(|subschema| allow_placeholders(subschema, placeholder));
+        }
+    }
+}
+
+/// Allow placeholders inside the subschema and let the subschema itself be a placeholder
+fn wrap(subschema: &mut Value, placeholder: &Value) {
+    // Boolean schemas either already accept a placeholder or reject every value
+    if subschema.is_boolean() {
+        return;
+    }
+
+    allow_placeholders(subschema, placeholder);
+    *subschema = with_placeholder(subschema.take(), placeholder);
+}
+
+fn with_placeholder(schema: Value, placeholder: &Value) -> Value {
+    let mut any_of = Map::new();
+    any_of.insert("anyOf".to_string(), Value::Array(vec![schema, placeholder.clone()]));
+    Value::Object(any_of)
+}
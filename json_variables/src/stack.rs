@@ -0,0 +1,71 @@
+use super::merge::MergeStrategy;
+use super::variables::Variables;
+
+/// Layers of variables with an explicit precedence, e.g. defaults, a file, a profile and command
+/// line overrides. Layers with a higher precedence are laid over the layers with a lower
+/// precedence, layers with the same precedence are laid over each other in the order they were
+/// pushed.
+///
+/// # Examples
+/// ```
+/// use serde_json::json;
+/// use json_variables::{MergeStrategy, VariablesStack};
+///
+/// let defaults = json_variables::from_json(json!({ "DB": { "host": "localhost", "port": 5432 } })).unwrap();
+/// let cli = json_variables::from_json(json!({ "DB": { "port": 5433 } })).unwrap();
+///
+/// let variables = VariablesStack::new()
+///     .with_layer(10, cli, MergeStrategy::DEEP)
+///     .with_layer(0, defaults, MergeStrategy::DEEP)
+///     .resolve();
+///
+/// assert_eq!(variables.replace(r#""${DB.host}:${DB.port}""#), r#""localhost:5433""#);
+/// ```
+#[derive(Debug, Default)]
+pub struct VariablesStack {
+    layers: Vec<Layer>
+}
+
+#[derive(Debug)]
+struct Layer {
+    precedence: i32,
+    variables: Variables,
+    strategy: MergeStrategy
+}
+
+impl VariablesStack {
+    pub fn new() -> VariablesStack {
+        VariablesStack::default()
+    }
+
+    /// Add a layer with the given precedence. The strategy decides how the layer is combined
+    /// with the layers of a lower precedence.
+    pub fn push(&mut self, precedence: i32, variables: Variables, strategy: MergeStrategy) -> &mut Self {
+        self.layers.push(Layer { precedence, variables, strategy });
+        self
+    }
+
+    /// Same as [`Self::push`], taking and returning the stack by value
+    pub fn with_layer(mut self, precedence: i32, variables: Variables, strategy: MergeStrategy) -> Self {
+        self.push(precedence, variables, strategy);
+        self
+    }
+
+    /// Combine the layers into a single set of variables. The pattern and coerce flag of the
+    /// layer with the lowest precedence are used.
+    pub fn resolve(&self) -> Variables {
+        let mut layers: Vec<&Layer> = self.layers.iter().collect();
+        layers.sort_by_key(|layer| layer.precedence);
+
+        let mut layers = layers.into_iter();
+        let Some(base) = layers.next() else {
+            return Variables::default();
+        };
+
+        let mut variables = base.variables.clone();
+        for layer in layers {
+            variables.layer(layer.variables.clone(), layer.strategy);
+        }
+        variables
+    }
+}
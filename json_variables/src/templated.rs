@@ -0,0 +1,59 @@
+use serde_json::Value;
+
+use super::{Error, Variables};
+
+/// Resolves the templated form of a type inside the scope of the variables of its parent.
+///
+/// Implemented by the `JsonVariables` derive, such that a derived struct can be embedded in the
+/// data of another derived struct through `#[variables(nested)]`. The variables block of the
+/// nested struct shadows the variables of its parent, variables of the parent which are not
+/// shadowed stay visible inside the nested struct.
+pub trait Templated {
+    /// Resolve the templated JSON `value` of `Self`, returning the plain JSON of `Self` with all
+    /// variables replaced
+    fn resolve_scoped(value: Value, scope: &Variables) -> Result<Value, Error>;
+
+    /// Run the `validate` hook of `Self` and of its nested fields, after the parsed struct is
+    /// deserialized
+    fn validate(&self) -> Result<(), Error>;
+}
+
+impl<T: Templated> Templated for Option<T> {
+    fn resolve_scoped(value: Value, scope: &Variables) -> Result<Value, Error> {
+        match value {
+            Value::Null => Ok(Value::Null),
+            value => T::resolve_scoped(value, scope)
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        self.as_ref().map_or(Ok(()), T::validate)
+    }
+}
+
+impl<T: Templated> Templated for Vec<T> {
+    fn resolve_scoped(value: Value, scope: &Variables) -> Result<Value, Error> {
+        match value {
+            Value::Array(array) => array
+                .into_iter()
+                .map(|value| T::resolve_scoped(value, scope))
+                .collect::<Result<Vec<Value>, Error>>()
+                .map(Value::Array),
+            _ => Err(Error::InvalidJson)
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        self.iter().try_for_each(T::validate)
+    }
+}
+
+impl<T: Templated> Templated for Box<T> {
+    fn resolve_scoped(value: Value, scope: &Variables) -> Result<Value, Error> {
+        T::resolve_scoped(value, scope)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        T::validate(self)
+    }
+}
@@ -1,20 +1,14 @@
 use serde_json::Value;
 
 /// The variable tree. Represents the tree structured variable system
-#[derive(Debug)]
+#[derive(Debug, Clone, Default)]
 pub struct VariableTree {
     pub root: usize,
     pub nodes: Vec<Node>
 }
 
-impl Default for VariableTree {
-    fn default() -> Self {
-        VariableTree { root: 0, nodes: Vec::new() }
-    }
-}
-
 /// The node object. Is a item in the [`VariableTree`] object
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Node {
     pub variable: String,
     pub value: Value,
@@ -27,7 +21,7 @@ impl VariableTree {
     }
 
     pub fn get_root(&self) -> Option<&Node> {
-        self.nodes.get(0)
+        self.nodes.first()
     }
 
     pub fn add_child(&mut self, parent: usize, variable: String, value: Value) -> usize {
@@ -43,42 +37,20 @@ impl VariableTree {
         id
     }
 
-    pub fn get_variable_value(&self, variable: &String) -> Option<Value> {
-        let variables: Vec<&str> = variable.split(".").collect();
-        let last = *variables.clone().last().unwrap(); 
+    pub fn get_variable_value(&self, variable: &str) -> Option<Value> {
         let mut current_node = self.root;
 
         // Iterate over given variable, in the format var.var.var. ....
-        for var in variables {
-            // If var exists, it should be a child of the current node
-            if let Some(next_node) = self.nodes.get(current_node) {
-
-                // Reset moved checker
-                let mut moved = false;
-
-                for child in next_node.children.clone() {
-                    if let Some(child_node) = self.nodes.get(child) {
-                        // Check if current child is the variable searched
-                        if child_node.variable.eq(last) {
-                            return Some(child_node.value.clone());
-                        }
-                        else if child_node.variable.eq(var) {
-                            moved = true;
-                            current_node = child;
-                            // Break out of children loop if next node is found
-                            break;
-                        }
-                    }
-                }
-
-                // If during the checking of the children, none was found which matched the next
-                // variable, then the variable doesn't exist
-                if !moved {
-                    return None;
-                }
-            }
+        for var in variable.split(".") {
+            // If var exists, it should be a child of the current node, otherwise the variable
+            // doesn't exist
+            let node = self.nodes.get(current_node)?;
+            current_node = node.children
+                .iter()
+                .copied()
+                .find(|child| self.nodes.get(*child).is_some_and(|child_node| child_node.variable == var))?;
         }
 
-        None 
+        self.nodes.get(current_node).map(|node| node.value.clone())
     }
 }
@@ -1,23 +1,54 @@
-use std::os::unix::process::parent_id;
-use regex::Regex;
-use serde::{de::Error, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use std::{borrow::Cow, ops::{Deref, DerefMut, Range}, path::PathBuf, sync::Arc};
+
+use regex::{Captures, Regex};
+use serde::{de::{DeserializeOwned, Error}, ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Map, Value};
 
+use super::coerce::{self, Coerce};
+use super::expression;
+use super::commands::{self, CommandPolicy};
+use super::files::{self, FileAccessPolicy};
+use super::functions::Functions;
+use super::path;
+use super::merge::{self, MergeStrategy};
+use super::builder::VariablesBuilder;
 use super::tree::VariableTree;
 
 
 /// The `Variables` struct. Holds the variables of the json file in a `serde_json::Value::Map`
 /// object
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Variables {
-    pub variables: Map<String, Value>,
+    variables: Arc<Map<String, Value>>,
     pub pattern: Regex,
-    tree: VariableTree
+    /// Convert strings produced by placeholders into numbers and booleans when the deserialized
+    /// type requests them, see [`Variables::apply_into`]
+    pub coerce: bool,
+    /// The built-in functions such as `now` and `uuid`, see [`Functions`]
+    pub functions: Functions,
+    /// Directory against which the relative paths of `${file:...}` and `${json:...}` are
+    /// resolved, the current directory if not set
+    pub base_dir: Option<PathBuf>,
+    /// The files which can be read by `${file:...}` and `${json:...}`, which are the files inside
+    /// the base directory by default, see [`FileAccessPolicy`]
+    pub file_access: FileAccessPolicy,
+    /// The commands which can be run by `${cmd:...}`, which are disabled by default, see
+    /// [`CommandPolicy`]
+    pub commands: CommandPolicy,
+    tree: Arc<VariableTree>,
+    /// Layers which stay on top of the variables of nested scopes, see
+    /// [`Variables::with_top_layer`]
+    top_layers: Arc<Vec<(Map<String, Value>, MergeStrategy)>>,
+    /// Variables of the loops and macros being applied, which shadow the variables with the same
+    /// top level name without copying the variables
+    locals: Map<String, Value>,
+    /// Macros which are being called, to detect recursion
+    calls: Vec<String>
 }
 
 impl Default for Variables {
     fn default() -> Self {
-        Variables { variables: Map::default(), pattern: default_pattern(), tree: VariableTree::default() }
+        Variables { variables: Arc::default(), pattern: default_pattern(), coerce: false, functions: Functions::default(), base_dir: None, file_access: FileAccessPolicy::default(), commands: CommandPolicy::default(), tree: Arc::default(), top_layers: Arc::default(), locals: Map::new(), calls: Vec::new() }
     }
 }
 
@@ -38,11 +69,12 @@ impl<'de> Deserialize<'de> for Variables {
             D: Deserializer<'de> {
         let helper  = VariablesDeserializer::deserialize(deserializer)?;
 
-        Ok(Variables::new(helper.variables, Some(helper.pattern.to_string())).unwrap())
+        Ok(Variables::with_pattern(helper.variables, helper.pattern))
     }
 }
 
-// Manual implementation to only parse variables on serialization
+// Manual implementation to only parse variables on serialization. The pattern is only written
+// when it differs from the default so the output can be deserialized back into the same variables
 impl Serialize for Variables {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
@@ -52,6 +84,9 @@ impl Serialize for Variables {
         for (k, v) in self.variables.iter() {
             map.serialize_entry(k, v)?;
         }
+        if self.pattern.as_str() != default_pattern().as_str() {
+            map.serialize_entry("pattern", self.pattern.as_str())?;
+        }
 
         map.end()
     }
@@ -67,11 +102,8 @@ impl Serialize for Variables {
 /// let json = r#"{ "${NAME}": "rust" }"#;
 /// let variables = json_variables::from_str(json).expect("Unable to parse string to variables");
 /// ```
-pub fn from_str(json: &str) -> Result<Variables, serde_json::Error> {
-    let mut variables: Variables = serde_json::from_str(json)?;
-    let tree = Variables::create_tree(&variables.variables);
-    variables.tree = tree;
-    Ok(variables)
+pub fn from_str(json: &str) -> Result<Variables, super::Error> {
+    Ok(serde_json::from_str(json)?)
 }
 
 /// Create type variables from serde_json `json!()` macro
@@ -107,42 +139,538 @@ where
     s.serialize_str(regex.as_str())
 }
 
+/// The default pattern `${<variable>}`, which matches any placeholder without braces, such as
+/// the path `${DB.hosts[0]}`, the selectors `[*]`, `[start:end]` and `[?key=="value"]`, or an
+/// expression.
+///
+/// Text which looks like a placeholder but is meant literally, such as the shell expansion
+/// `${HOME:-/tmp}`, has to be escaped as `$${HOME:-/tmp}`, which results in `${HOME:-/tmp}`.
 pub fn default_pattern() -> Regex {
-    // Matches the pattern ${<variable(.variable>)} where (.variable) is a possible "depth"
-    // accessing of variables inside a map
-    Regex::new(r"\$\{([a-zA-Z0-9_.]+)\}").unwrap()
+    Regex::new(r"\$\{([^{}]+)\}").unwrap()
 }
 
 impl Variables {
     pub fn new(variables: Map<String, Value>, pattern: Option<String>) -> Result<Variables, super::Error> {
         let var_pattern;
         if let Some(pattern) = pattern {
-            var_pattern = Regex::new(&pattern).map_err(|_| super::Error::InvalidPattern(pattern))?;
+            var_pattern = Regex::new(&pattern).map_err(|err| super::Error::InvalidPattern(pattern.clone(), err))?;
         }
         else {
             var_pattern = default_pattern();
         }
 
+        Ok(Self::with_pattern(variables, var_pattern))
+    }
+
+    /// Create variables from a Rust value, e.g. a struct of parsed command line arguments,
+    /// which serializes into a JSON object
+    ///
+    /// # Examples
+    /// ```
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Args {
+    ///     host: String,
+    ///     port: u16
+    /// }
+    ///
+    /// let args = Args { host: "localhost".to_string(), port: 8080 };
+    /// let variables = json_variables::Variables::from_serialize(&args).expect("Unable to serialize variables");
+    ///
+    /// assert_eq!(variables.get::<u16>("port").unwrap(), 8080);
+    /// ```
+    pub fn from_serialize(value: &impl Serialize) -> Result<Variables, super::Error> {
+        from_json(serde_json::to_value(value)?)
+    }
 
+    /// Create a builder to define variables one by one
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::Variables::builder()
+    ///     .variable("NAME", "John")
+    ///     .variable("DB.port", 5432)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(variables.get_value("DB"), Some(&json!({ "port": 5432 })));
+    /// ```
+    pub fn builder() -> VariablesBuilder {
+        VariablesBuilder::default()
+    }
+
+    fn with_pattern(variables: Map<String, Value>, pattern: Regex) -> Variables {
         let tree = Self::create_tree(&variables);
 
-        Ok(
-            Variables 
-            { 
-                pattern: var_pattern,
-                variables,
-                tree,
+        Variables 
+        { 
+            pattern,
+            variables: Arc::new(variables),
+            coerce: false,
+            functions: Functions::default(),
+            base_dir: None,
+            file_access: FileAccessPolicy::default(),
+            commands: CommandPolicy::default(),
+            tree,
+            top_layers: Arc::default(),
+            locals: Map::new(),
+            calls: Vec::new(),
+        }
+    }
+}
+
+impl Variables {
+    /// The variables as a JSON map
+    pub fn as_map(&self) -> &Map<String, Value> {
+        &self.variables
+    }
+
+    /// Take the variables as a JSON map
+    pub fn into_map(self) -> Map<String, Value> {
+        Arc::unwrap_or_clone(self.variables)
+    }
+
+    /// Get the value of the variable, in the format var.var.var, where a number indexes an array
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::from_json(json!({ "DB": { "hosts": ["a", "b"] } })).unwrap();
+    ///
+    /// assert_eq!(variables.get_value("DB.hosts.1"), Some(&json!("b")));
+    /// assert_eq!(variables.get_value("DB.port"), None);
+    /// ```
+    pub fn get_value(&self, path: &str) -> Option<&Value> {
+        value_at(&self.variables, path)
+    }
+
+    /// Get the value of the variable, in the format var.var.var, as the given type
+    ///
+    /// Returns [`Error::UndefinedVariable`](super::Error::UndefinedVariable) if the variable is
+    /// not defined and [`Error::InvalidVariable`](super::Error::InvalidVariable) if its value
+    /// does not deserialize into the type.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::from_json(json!({ "DB": { "pool": 10, "hosts": ["a", "b"] } })).unwrap();
+    ///
+    /// assert_eq!(variables.get::<u32>("DB.pool").unwrap(), 10);
+    /// assert_eq!(variables.get::<Vec<String>>("DB.hosts").unwrap(), ["a", "b"]);
+    /// assert!(variables.get::<String>("DB.pool").is_err());
+    /// ```
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, super::Error> {
+        let value = path::select(&self.variables, path)?.ok_or_else(|| super::Error::UndefinedVariable(path.to_string()))?;
+        T::deserialize(value).map_err(|err| super::Error::InvalidVariable(path.to_string(), err))
+    }
+
+    /// Get mutable access to the value of the variable, in the format var.var.var. The lookups
+    /// are updated once the returned guard is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let mut variables = json_variables::from_json(json!({ "DB": { "port": 5432 } })).unwrap();
+    /// if let Some(mut port) = variables.get_mut("DB.port") {
+    ///     *port = json!(5433);
+    /// }
+    ///
+    /// assert_eq!(variables.get_value("DB.port"), Some(&json!(5433)));
+    /// ```
+    pub fn get_mut(&mut self, path: &str) -> Option<VariableMut<'_>> {
+        let value = value_at_mut(Arc::make_mut(&mut self.variables), path)?.take();
+        Some(VariableMut { variables: self, path: path.to_string(), value })
+    }
+
+    /// Insert a variable, in the format var.var.var, returning the previous value. Objects are
+    /// created for the missing parents and a number indexes an array, where the index after the
+    /// last element appends to the array.
+    ///
+    /// Returns [`Error::InvalidPath`](super::Error::InvalidPath) if a parent is not an object or
+    /// an array, or the index is out of bounds.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let mut variables = json_variables::Variables::default();
+    /// variables.insert("DB.host", json!("localhost")).unwrap();
+    ///
+    /// assert_eq!(variables.replace(r#""${DB.host}""#), r#""localhost""#);
+    /// ```
+    pub fn insert(&mut self, path: &str, value: Value) -> Result<Option<Value>, super::Error> {
+        let previous = insert_path(Arc::make_mut(&mut self.variables), path, value)?;
+        self.tree = Self::create_tree(&self.variables);
+        Ok(previous)
+    }
+
+    /// Remove a variable, in the format var.var.var, returning its value
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let mut variables = json_variables::from_json(json!({ "DB": { "host": "localhost", "port": 5432 } })).unwrap();
+    ///
+    /// assert_eq!(variables.remove("DB.port"), Some(json!(5432)));
+    /// assert!(!variables.contains("DB.port"));
+    /// ```
+    pub fn remove(&mut self, path: &str) -> Option<Value> {
+        let variables = Arc::make_mut(&mut self.variables);
+        let removed = match path.rsplit_once('.') {
+            Some((parent, last)) => match value_at_mut(variables, parent)? {
+                Value::Object(map) => map.remove(last),
+                Value::Array(array) => {
+                    let index = last.parse::<usize>().ok().filter(|index| *index < array.len())?;
+                    Some(array.remove(index))
+                }
+                _ => None
+            },
+            None => variables.remove(path)
+        }?;
+
+        self.tree = Self::create_tree(&self.variables);
+        Some(removed)
+    }
+}
+
+/// Mutable access to the value of a variable, see [`Variables::get_mut`]. The lookups of the
+/// variables are updated when it is dropped.
+#[derive(Debug)]
+pub struct VariableMut<'a> {
+    variables: &'a mut Variables,
+    path: String,
+    value: Value
+}
+
+impl Deref for VariableMut<'_> {
+    type Target = Value;
+
+    fn deref(&self) -> &Value {
+        &self.value
+    }
+}
+
+impl DerefMut for VariableMut<'_> {
+    fn deref_mut(&mut self) -> &mut Value {
+        &mut self.value
+    }
+}
+
+impl Drop for VariableMut<'_> {
+    fn drop(&mut self) {
+        if let Some(value) = value_at_mut(Arc::make_mut(&mut self.variables.variables), &self.path) {
+            *value = self.value.take();
+        }
+        self.variables.tree = Variables::create_tree(&self.variables.variables);
+    }
+}
+
+impl Extend<(String, Value)> for Variables {
+    /// Insert the top level variables, replacing variables with the same name
+    fn extend<I: IntoIterator<Item = (String, Value)>>(&mut self, iter: I) {
+        Arc::make_mut(&mut self.variables).extend(iter);
+        self.tree = Self::create_tree(&self.variables);
+    }
+}
+
+impl FromIterator<(String, Value)> for Variables {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        Variables::with_pattern(iter.into_iter().collect(), default_pattern())
+    }
+}
+
+fn value_at<'a>(variables: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut segments = path.split('.');
+    let mut value = variables.get(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            Value::Object(map) => map.get(segment)?,
+            Value::Array(array) => array.get(segment.parse::<usize>().ok()?)?,
+            _ => return None
+        };
+    }
+    Some(value)
+}
+
+fn value_at_mut<'a>(variables: &'a mut Map<String, Value>, path: &str) -> Option<&'a mut Value> {
+    let mut segments = path.split('.');
+    let mut value = variables.get_mut(segments.next()?)?;
+    for segment in segments {
+        value = match value {
+            Value::Object(map) => map.get_mut(segment)?,
+            Value::Array(array) => array.get_mut(segment.parse::<usize>().ok()?)?,
+            _ => return None
+        };
+    }
+    Some(value)
+}
+
+/// Insert the value at the path, creating objects for the missing parents
+pub(crate) fn insert_path(variables: &mut Map<String, Value>, path: &str, value: Value) -> Result<Option<Value>, super::Error> {
+    let invalid = || super::Error::InvalidPath(path.to_string());
+    let Some((parent, last)) = path.rsplit_once('.') else {
+        return Ok(variables.insert(path.to_string(), value));
+    };
+
+    let mut segments = parent.split('.');
+    let first = segments.next().ok_or_else(invalid)?;
+    let mut current = variables.entry(first).or_insert_with(|| Value::Object(Map::new()));
+    for segment in segments {
+        current = match current {
+            Value::Object(map) => map.entry(segment).or_insert_with(|| Value::Object(Map::new())),
+            Value::Array(array) => segment.parse::<usize>().ok().and_then(|index| array.get_mut(index)).ok_or_else(invalid)?,
+            _ => return Err(invalid())
+        };
+    }
+
+    match current {
+        Value::Object(map) => Ok(map.insert(last.to_string(), value)),
+        Value::Array(array) => match last.parse::<usize>() {
+            Ok(index) if index < array.len() => Ok(Some(std::mem::replace(&mut array[index], value))),
+            Ok(index) if index == array.len() => {
+                array.push(value);
+                Ok(None)
             }
-        )
+            _ => Err(invalid())
+        },
+        _ => Err(invalid())
+    }
+}
+
+impl Variables {
+    /// Check if the variable, in the format var.var.var or with selectors such as `SERVERS[0]`,
+    /// is defined. Only the definitions are checked, expressions, functions, files and commands
+    /// are never evaluated.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::from_json(json!({ "DB": { "url": "localhost" } })).unwrap();
+    ///
+    /// assert!(variables.contains("DB.url"));
+    /// assert!(!variables.contains("DB.port"));
+    /// assert!(!variables.contains("hostname()"));
+    /// ```
+    pub fn contains(&self, variable: &str) -> bool {
+        match path::is_selector(variable) {
+            true => matches!(path::select_scoped(&self.locals, &self.variables, variable), Ok(Some(_))),
+            false => self.local(variable).map_or_else(|| self.tree.get_variable_value(variable).is_some(), |value| value.is_some())
+        }
+    }
+
+    /// Create a copy of the variables where the given overrides take precedence.
+    ///
+    /// An override replaces the variable with the same top level name as a whole, objects are
+    /// not merged. Variables which are not overridden are kept and the pattern of `self` is used.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::from_json(json!({ "HOST": "localhost", "PORT": 8080 })).unwrap();
+    /// let overrides = json!({ "HOST": "example.com" });
+    /// let variables = variables.with_overrides(overrides.as_object().unwrap());
+    ///
+    /// assert_eq!(variables.replace(r#""${HOST}:${PORT}""#), r#""example.com:8080""#);
+    /// ```
+    pub fn with_overrides(&self, overrides: &Map<String, Value>) -> Variables {
+        let mut variables = self.clone();
+        variables.layer_map(overrides.clone(), MergeStrategy::REPLACE);
+        variables
+    }
+
+    /// Create a copy of the variables with the layer laid over them, which also stays on top of
+    /// the variables of the nested scopes created through [`Self::with_scope`]. Runtime
+    /// overrides are kept on top in this way, such that they take precedence over the variables
+    /// defined by nested blocks as well.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    /// use json_variables::MergeStrategy;
+    ///
+    /// let variables = json_variables::from_json(json!({ "HOST": "localhost" })).unwrap();
+    /// let overrides = json!({ "HOST": "example.com" });
+    /// let variables = variables.with_top_layer(overrides.as_object().unwrap(), MergeStrategy::REPLACE);
+    ///
+    /// let nested = json!({ "HOST": "nested.local", "PORT": 8080 });
+    /// let scope = variables.with_scope(nested.as_object().unwrap());
+    /// assert_eq!(scope.replace(r#""${HOST}:${PORT}""#), r#""example.com:8080""#);
+    /// ```
+    pub fn with_top_layer(&self, layer: &Map<String, Value>, strategy: MergeStrategy) -> Variables {
+        let mut variables = self.clone();
+        variables.layer_map(layer.clone(), strategy);
+        Arc::make_mut(&mut variables.top_layers).push((layer.clone(), strategy));
+        variables
+    }
+
+    /// Create the scope of a nested block, where the variables of the block shadow these
+    /// variables in the same way as [`Self::with_overrides`]. The layers added through
+    /// [`Self::with_top_layer`] are laid over the result again, such that they keep precedence.
+    /// The scope is part of the same render, so it shares the state of the [`Functions`], such as
+    /// the memoized values, with these variables.
+    pub fn with_scope(&self, variables: &Map<String, Value>) -> Variables {
+        let mut scope = self.share();
+        let merged = Arc::make_mut(&mut scope.variables);
+        merge::merge_map(merged, variables.clone(), MergeStrategy::REPLACE);
+        for (layer, strategy) in self.top_layers.iter() {
+            merge::merge_map(merged, layer.clone(), *strategy);
+        }
+        scope.tree = Self::create_tree(&scope.variables);
+        scope
+    }
+
+    /// Scope of a loop or macro with the given local variables, which is part of the same render.
+    /// The variables and their tree are shared with `self`, the locals are only laid over them
+    /// by [`Self::lookup`].
+    fn with_locals(&self, locals: Map<String, Value>) -> Variables {
+        let mut scope = self.share();
+        scope.locals.extend(locals);
+        scope
+    }
+
+    /// Copy of the variables for a scope of the same render, which shares the state of the
+    /// [`Functions`] with these variables
+    pub(crate) fn share(&self) -> Variables {
+        let mut scope = self.clone();
+        scope.functions = self.functions.share();
+        scope
+    }
+
+    /// The value of a plain path if its top level name is a local variable, where the inner
+    /// `None` means that the local variable does not define the path
+    fn local(&self, variable: &str) -> Option<Option<&Value>> {
+        let name = variable.split('.').next()?;
+        self.locals.contains_key(name).then(|| value_at(&self.locals, variable))
+    }
+
+    /// Lay the other variables over these variables, where the variables of `other` take
+    /// precedence. Variables defined by only one of both are kept, variables defined by both are
+    /// combined following the strategy. The pattern and coerce flag of `self` are kept.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    /// use json_variables::{ArrayMerge, MergeStrategy};
+    ///
+    /// let mut variables = json_variables::from_json(json!({ "DB": { "host": "localhost", "port": 5432 }, "TAGS": ["a"] })).unwrap();
+    /// let other = json_variables::from_json(json!({ "DB": { "port": 5433 }, "TAGS": ["b"] })).unwrap();
+    /// variables.layer(other, MergeStrategy::DEEP.arrays(ArrayMerge::Append));
+    ///
+    /// assert_eq!(variables.replace(r#""${DB.host}:${DB.port}""#), r#""localhost:5433""#);
+    /// assert_eq!(variables.replace(r#""${TAGS.1}""#), r#""b""#);
+    /// ```
+    pub fn layer(&mut self, other: Variables, strategy: MergeStrategy) {
+        self.layer_map(other.into_map(), strategy);
+    }
+
+    fn layer_map(&mut self, other: Map<String, Value>, strategy: MergeStrategy) {
+        merge::merge_map(Arc::make_mut(&mut self.variables), other, strategy);
+        self.tree = Self::create_tree(&self.variables);
+    }
+}
+
+/// Escape of a placeholder which is kept literally, such that `$${HOME}` results in `${HOME}`
+pub const ESCAPE: char = '$';
+
+/// Whether the placeholder found inside JSON text extends over the end of a JSON string
+fn leaves_string(placeholder: &str) -> bool {
+    let mut escaped = false;
+    for char in placeholder.chars() {
+        match char {
+            '\n' => return true,
+            '"' if !escaped => return true,
+            _ => {}
+        }
+        escaped = char == '\\' && !escaped;
+    }
+    false
+}
+
+/// Maximum depth of placeholders inside the names of other placeholders, see
+/// [`Variables::apply`]
+pub const MAX_NESTING_DEPTH: usize = 16;
+
+/// Key of the condition which includes an object in the data only when it holds
+pub const IF_KEY: &str = "$if";
+
+/// Key of the value which replaces an object when its `$if` condition does not hold
+pub const ELSE_KEY: &str = "$else";
+
+/// Key of the condition which keeps an element of an array only when it holds
+pub const WHEN_KEY: &str = "$when";
+
+/// Key of the loop definition `<variable> in <array or object>`, which repeats the `$do` value
+/// for every item
+pub const FOR_KEY: &str = "$for";
+
+/// Key of the value which is repeated by a `$for` loop
+pub const DO_KEY: &str = "$do";
+
+/// Variable holding the position of the current item inside a `$for` loop
+pub const INDEX_VARIABLE: &str = "$index";
+
+/// Variable holding the key of the current item inside a `$for` loop over an object, or its
+/// position for an array
+pub const KEY_VARIABLE: &str = "$key";
+
+/// Key of the macros inside the variables, where every macro is defined by its signature
+/// `name(parameter, ...)` and its body
+pub const MACROS_KEY: &str = "$macros";
+
+/// Key of the profiles inside the variables
+pub const PROFILES_KEY: &str = "$profiles";
+
+impl Variables {
+    /// Create a copy of the variables with the given profile of the `$profiles` variable laid
+    /// over the base variables. Objects are merged, such that a profile only has to define the
+    /// values which differ from the base variables, other values are replaced by the profile.
+    /// The profile stays on top of nested scopes, see [`Self::with_top_layer`].
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::from_json(json!({
+    ///     "DB": { "host": "localhost", "port": 5432 },
+    ///     "$profiles": {
+    ///         "prod": { "DB": { "host": "db.example.com" } }
+    ///     }
+    /// })).unwrap();
+    ///
+    /// let prod = variables.with_profile("prod").unwrap();
+    /// assert_eq!(prod.replace(r#""${DB.host}:${DB.port}""#), r#""db.example.com:5432""#);
+    /// assert!(variables.with_profile("staging").is_err());
+    /// ```
+    pub fn with_profile(&self, profile: &str) -> Result<Variables, super::Error> {
+        let mut variables = self.clone();
+        let overlay = Arc::make_mut(&mut variables.variables)
+            .remove(PROFILES_KEY)
+            .and_then(|mut profiles| profiles.get_mut(profile).map(Value::take))
+            .ok_or_else(|| super::Error::UnknownProfile(profile.to_string()))?;
+
+        let Value::Object(overlay) = overlay else {
+            return Err(super::Error::InvalidJson);
+        };
+
+        Ok(variables.with_top_layer(&overlay, MergeStrategy::DEEP))
     }
 }
 
 impl Variables {
     /// Generate the redundant variable tree
-    fn create_tree(variables: &Map<String, Value>) -> VariableTree {
+    fn create_tree(variables: &Map<String, Value>) -> Arc<VariableTree> {
         let mut tree = VariableTree::new(Value::Object(variables.clone()));
         Self::insert_variables(0, variables.clone(), &mut tree);
-        tree
+        Arc::new(tree)
     }
 
     /// Recursion to create the variable tree 
@@ -166,43 +694,468 @@ impl Variables {
 
     /// Replace mentions of variables of the pattern(<variable>) with their true value as given
     /// in the variable tree
+    ///
+    /// # Panics
+    ///
+    /// Panics if a variable is referenced which is not defined. Use [`Variables::try_replace`] to
+    /// handle this case as an error instead.
     pub fn replace(&self, config: impl Into<String>) -> String {
-        let mut config = config.into();
-        // Get all pattern matches
-        let mut variables = Vec::<(String, String)>::new();
-        for variable in self.pattern.captures_iter(&config) {
-            // Index corresponds to the <variable> inside the pattern(<variable>)
-            variables.push((variable[0].to_string(), variable[1].to_string()));
-        }
-
-        // Iterate over all pattern matches, get the value and replace all mentions of that
-        // variable with its value
-        for (pattern_variable, variable) in variables {
-            let value = self.tree.get_variable_value(&variable);
-            if let Some(value) = value {
-                // Replace all "pattern(<variable>)" references
-                let pattern_variable_quote = format!("\"{pattern_variable}\"");
-                while config.contains(&pattern_variable_quote) {
-                    config = config.replace(&pattern_variable_quote, &value.to_string());
+        self.try_replace(config).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Replace mentions of variables of the pattern(<variable>) with their true value as given
+    /// in the variable tree, returning [`Error::UndefinedVariable`](super::Error::UndefinedVariable)
+    /// if a referenced variable is not defined
+    pub fn try_replace(&self, config: impl Into<String>) -> Result<String, super::Error> {
+        self.functions.begin_render();
+        let config = config.into();
+        let config = self.resolve_names(&config, true)?;
+
+        let mut result = String::with_capacity(config.len());
+        let mut last = 0;
+        for captures in self.placeholders(&config, true) {
+            let whole = captures.get(0).unwrap();
+            // Quotes of filters are escaped inside the JSON text
+            let value = self.lookup(&captures[1].replace("\\\"", "\""))?;
+
+            // A "pattern(<variable>)" reference is replaced by the JSON value as a whole
+            let quoted = config[last..whole.start()].ends_with('"') && config[whole.end()..].starts_with('"');
+            if quoted {
+                result.push_str(&self.unescape(&config[last..whole.start() - 1]));
+                result.push_str(&value.to_string());
+                last = whole.end() + 1;
+                continue;
+            }
+
+            // A "...pattern(var)..." reference is formatted into the string, where the quotation
+            // marks of a string value are not needed
+            result.push_str(&self.unescape(&config[last..whole.start()]));
+            match value {
+                Value::String(_) => {
+                    let value = value.to_string();
+                    result.push_str(&value[1..value.len() - 1]);
                 }
+                value => result.push_str(&value.to_string())
+            }
+            last = whole.end();
+        }
+        result.push_str(&self.unescape(&config[last..]));
+
+        Ok(result)
+    }
+}
+
+impl Variables {
+    /// Replace mentions of variables inside the given JSON value. A string which only consists
+    /// of a single pattern(<variable>) is replaced by the value of the variable, otherwise the
+    /// values of the variables are formatted into the string. Object keys are formatted as well.
+    /// Placeholders inside the name of another placeholder, such as `${DB_${ENV}}`, are resolved
+    /// first, up to a depth of [`MAX_NESTING_DEPTH`].
+    ///
+    /// An object with a `$if` condition is only included when the condition holds, otherwise
+    /// it is replaced by its `$else` value or left out entirely. An element of an array with a
+    /// `$when` condition is dropped when the condition does not hold.
+    ///
+    /// An object with a `$for` loop, such as `{ "$for": "srv in ${SERVERS}", "$do": ... }`, is
+    /// replaced by the `$do` value for every item of the array or object, where the item is
+    /// available as `${srv}` and its position as `${$index}`. Inside an array, including the `$do`
+    /// value of an outer loop, the elements of a loop are added to the array itself.
+    ///
+    /// Macros defined in the `$macros` variable, such as `"service(name, port)": { ... }`, are
+    /// called from a placeholder like `${service('api', 8080)}`, which results in the body of
+    /// the macro with the arguments available through the names of the parameters. The built-in
+    /// [`Functions`] are called in the same way.
+    ///
+    /// The placeholders `${file:./certs/ca.pem}` and `${json:./regions.json#/eu}` inline the
+    /// content of a file as a string, or the parsed JSON of a file, optionally at a JSON pointer.
+    /// Relative paths are resolved against [`Variables::base_dir`], and by default only the files
+    /// inside of it can be read, see [`Variables::file_access`]. Once enabled through
+    /// [`Variables::commands`], `${cmd:git rev-parse --short HEAD}` results in the trimmed
+    /// output of the command.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let variables = json_variables::from_json(json!({ "NL": { "short": "NL" } })).unwrap();
+    /// let result = variables.apply(json!({ "country": "${NL}", "label": "country ${NL.short}" })).unwrap();
+    ///
+    /// assert_eq!(result, json!({ "country": { "short": "NL" }, "label": "country NL" }));
+    /// ```
+    pub fn apply(&self, value: Value) -> Result<Value, super::Error> {
+        self.functions.begin_render();
+        self.apply_value(value, false)
+    }
+
+    /// Replace mentions of variables inside the given JSON value as done by [`Variables::apply`]
+    /// and deserialize the result.
+    ///
+    /// If [`Variables::coerce`] is set, strings produced by placeholders are converted into the
+    /// numbers and booleans requested by `T`, while strings written in the data are left
+    /// untouched. Coercion is not available inside untagged or internally tagged enums and
+    /// flattened fields.
+    ///
+    /// # Examples
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// let mut variables = json_variables::from_json(json!({ "PORT": "8080" })).unwrap();
+    /// variables.coerce = true;
+    ///
+    /// let port: u16 = variables.apply_into(json!("${PORT}")).unwrap();
+    /// assert_eq!(port, 8080);
+    /// ```
+    pub fn apply_into<T: DeserializeOwned>(&self, value: Value) -> Result<T, super::Error> {
+        self.functions.begin_render();
+        let value = self.apply_value(value, self.coerce)?;
+        self.deserialize_applied(value)
+    }
 
-                let mut value_string = value.to_string();
-                // If value is serde_json::Value::String, then remove quotation marks because at
-                // this point, the variable is concatinated with strings, therefor the quotation
-                // marks are not needed
-                if value.is_string() {
-                    value_string = value_string.replace("\"", "");
+    /// Deserialize a value which may contain strings marked for coercion
+    pub(crate) fn deserialize_applied<T: DeserializeOwned>(&self, value: Value) -> Result<T, super::Error> {
+        // Nested scopes may coerce even if these variables do not
+        match self.coerce || coerce::contains_marks(&value) {
+            true => Ok(T::deserialize(Coerce(value))?),
+            false => Ok(serde_json::from_value(value)?)
+        }
+    }
+
+    /// Replace mentions of variables, marking strings produced by placeholders for coercion if
+    /// `mark` is set
+    pub(crate) fn apply_value(&self, value: Value, mark: bool) -> Result<Value, super::Error> {
+        match value {
+            Value::String(string) => self.apply_str(string, mark),
+            Value::Array(array) => {
+                let mut result = Vec::with_capacity(array.len());
+                for value in array {
+                    self.apply_element(value, mark, &mut result)?;
                 }
-                // Replace all "...pattern(var)..." references
-                while config.contains(&pattern_variable) {
-                    config = config.replace(&pattern_variable, &value_string);
+                Ok(Value::Array(result))
+            }
+            Value::Object(map) => Ok(self.apply_object(map, mark)?.unwrap_or(Value::Null)),
+            value => Ok(value)
+        }
+    }
+
+    /// Replace an element of an array and add it to the result. A dropped element is not added,
+    /// while a loop adds all of its elements.
+    fn apply_element(&self, value: Value, mark: bool, result: &mut Vec<Value>) -> Result<(), super::Error> {
+        let Value::Object(mut map) = value else {
+            result.push(self.apply_value(value, mark)?);
+            return Ok(());
+        };
+
+        if let Some(condition) = map.remove(WHEN_KEY)
+            && !self.condition(condition)? {
+            return Ok(());
+        }
+
+        if map.contains_key(FOR_KEY) {
+            return self.apply_loop(map, mark, result);
+        }
+
+        result.extend(self.apply_object(map, mark)?);
+        Ok(())
+    }
+
+    /// Expand a `$for` loop, adding the `$do` value for every item to the result. The item is
+    /// available as the loop variable, together with `$index` and `$key`, which shadow the
+    /// variables with the same name.
+    fn apply_loop(&self, mut map: Map<String, Value>, mark: bool, result: &mut Vec<Value>) -> Result<(), super::Error> {
+        let definition = map.remove(FOR_KEY).unwrap_or_default();
+        let invalid = || super::Error::InvalidLoop(definition.to_string());
+
+        let body = map.remove(DO_KEY).ok_or_else(invalid)?;
+        let (name, source) = definition.as_str()
+            .and_then(|definition| definition.split_once(" in "))
+            .map(|(name, source)| (name.trim(), source.trim()))
+            .ok_or_else(invalid)?;
+        if !map.is_empty() || name.is_empty() || !name.chars().all(|char| char.is_alphanumeric() || char == '_') {
+            return Err(invalid());
+        }
+
+        let source = match self.placeholders(source, false).next().is_some() {
+            true => self.apply_str(source.to_string(), false)?,
+            false => self.lookup(source)?
+        };
+        let items: Vec<(Value, Value)> = match source {
+            Value::Array(array) => array.into_iter().enumerate().map(|(index, item)| (Value::from(index), item)).collect(),
+            Value::Object(map) => map.into_iter().map(|(key, item)| (Value::String(key), item)).collect(),
+            _ => return Err(invalid())
+        };
+
+        for (index, (key, item)) in items.into_iter().enumerate() {
+            let mut locals = Map::new();
+            locals.insert(name.to_string(), item);
+            locals.insert(INDEX_VARIABLE.to_string(), Value::from(index));
+            locals.insert(KEY_VARIABLE.to_string(), key);
+
+            self.with_locals(locals).apply_element(body.clone(), mark, result)?;
+        }
+        Ok(())
+    }
+
+    /// Replace an object, where `None` excludes the object because its `$if` condition does
+    /// not hold and it has no `$else`
+    fn apply_object(&self, mut map: Map<String, Value>, mark: bool) -> Result<Option<Value>, super::Error> {
+        if let Some(condition) = map.remove(IF_KEY) {
+            let otherwise = map.remove(ELSE_KEY);
+            if !self.condition(condition)? {
+                return otherwise.map(|value| self.apply_value(value, mark)).transpose();
+            }
+        }
+
+        if map.contains_key(FOR_KEY) {
+            let mut result = Vec::new();
+            self.apply_loop(map, mark, &mut result)?;
+            return Ok(Some(Value::Array(result)));
+        }
+
+        let mut result = Map::new();
+        for (key, value) in map {
+            let value = match value {
+                Value::Object(map) => match self.apply_object(map, mark)? {
+                    Some(value) => value,
+                    None => continue
+                },
+                value => self.apply_value(value, mark)?
+            };
+            result.insert(self.interpolate(&key)?, value);
+        }
+        Ok(Some(Value::Object(result)))
+    }
+
+    /// Evaluate the condition of `$if` or `$when`
+    fn condition(&self, condition: Value) -> Result<bool, super::Error> {
+        match self.apply_value(condition.clone(), false)? {
+            Value::Bool(bool) => Ok(bool),
+            _ => Err(super::Error::InvalidCondition(condition.to_string()))
+        }
+    }
+
+    /// Replace a JSON string, keeping the type of the variable if the string is a single
+    /// pattern(<variable>)
+    fn apply_str(&self, string: String, mark: bool) -> Result<Value, super::Error> {
+        let string = match self.resolve_names(&string, false)? {
+            Cow::Owned(resolved) => resolved,
+            Cow::Borrowed(_) => string
+        };
+
+        match self.placeholders(&string, false).next() {
+            Some(captures) if captures.get(0).is_some_and(|whole| whole.start() == 0 && whole.end() == string.len()) => {
+                return match self.lookup(&captures[1])? {
+                    Value::String(value) if mark => Ok(coerce::mark(value)),
+                    value => Ok(value)
+                };
+            }
+            Some(_) => {}
+            None => return Ok(Value::String(self.unescape(&string).into_owned()))
+        }
+
+        let string = self.interpolate(&string)?;
+        match mark {
+            true => Ok(coerce::mark(string)),
+            false => Ok(Value::String(string))
+        }
+    }
+
+    /// Format the values of all variables mentioned in the string into the string
+    fn interpolate(&self, string: &str) -> Result<String, super::Error> {
+        let string = &*self.resolve_names(string, false)?;
+        let mut result = String::with_capacity(string.len());
+        let mut last = 0;
+        for captures in self.placeholders(string, false) {
+            let whole = captures.get(0).unwrap();
+            result.push_str(&self.unescape(&string[last..whole.start()]));
+            match self.lookup(&captures[1])? {
+                Value::String(value) => result.push_str(&value),
+                value => result.push_str(&value.to_string())
+            }
+            last = whole.end();
+        }
+        result.push_str(&self.unescape(&string[last..]));
+
+        Ok(result)
+    }
+
+    /// The placeholders of the string, skipping escaped placeholders such as `$${HOME}`. Inside
+    /// JSON `text`, a placeholder does not extend over the end of a JSON string, i.e. it can not
+    /// contain an unescaped quote or a newline.
+    fn placeholders<'s>(&'s self, string: &'s str, text: bool) -> impl Iterator<Item = Captures<'s>> {
+        self.pattern.captures_iter(string).filter(move |captures| {
+            let whole = captures.get(0).unwrap();
+            let escaped = string[..whole.start()].ends_with(ESCAPE);
+            let crosses = text && leaves_string(whole.as_str());
+            !escaped && !crosses
+        })
+    }
+
+    /// Remove the escape of the escaped placeholders, such that `$${HOME}` results in `${HOME}`
+    fn unescape<'s>(&self, string: &'s str) -> Cow<'s, str> {
+        let escaped: Vec<usize> = self.pattern.find_iter(string)
+            .map(|found| found.start())
+            .filter(|start| string[..*start].ends_with(ESCAPE))
+            .collect();
+        if escaped.is_empty() {
+            return Cow::Borrowed(string);
+        }
+
+        let mut result = String::with_capacity(string.len());
+        let mut last = 0;
+        for start in escaped {
+            result.push_str(&string[last..start - ESCAPE.len_utf8()]);
+            last = start;
+        }
+        result.push_str(&string[last..]);
+        Cow::Owned(result)
+    }
+
+    /// Resolve the placeholders inside the names of other placeholders, such as `${ENV}` in
+    /// `${DB_${ENV}}`, innermost first, such that only placeholders with a plain name are left.
+    ///
+    /// Commands may not contain resolved values, as a value such as `main --output=/tmp/file`
+    /// would otherwise add arguments to `${cmd:git log ${BRANCH}}`.
+    fn resolve_names<'a>(&self, string: &'a str, text: bool) -> Result<Cow<'a, str>, super::Error> {
+        let mut string = Cow::Borrowed(string);
+        for depth in 0.. {
+            let nested = self.nested_placeholders(&string, text);
+            if nested.is_empty() {
+                break;
+            }
+            if depth == MAX_NESTING_DEPTH {
+                let (range, _) = &nested[0];
+                return Err(super::Error::NestingTooDeep(string[range.clone()].to_string()));
+            }
+
+            let mut resolved = String::with_capacity(string.len());
+            let mut substituted = Vec::with_capacity(nested.len());
+            let mut last = 0;
+            for (range, name) in nested {
+                resolved.push_str(&string[last..range.start]);
+                let start = resolved.len();
+                match self.lookup(&name)? {
+                    Value::String(value) => resolved.push_str(&value),
+                    value => resolved.push_str(&value.to_string())
                 }
+                substituted.push(start..resolved.len());
+                last = range.end;
             }
-            else {
-                panic!("Variable {variable} called but never defined");
+            resolved.push_str(&string[last..]);
+
+            let command = self.placeholders(&resolved, text)
+                .filter(|captures| captures[1].starts_with(commands::CMD_PREFIX))
+                .find(|captures| {
+                    let whole = captures.get(0).unwrap().range();
+                    substituted.iter().any(|range| range.start < whole.end && whole.start < range.end)
+                });
+            if let Some(captures) = command {
+                let command = captures[1][commands::CMD_PREFIX.len()..].trim().to_string();
+                return Err(super::Error::CommandDenied(command, "placeholders cannot be nested inside a command".to_string()));
             }
+
+            string = Cow::Owned(resolved);
         }
 
-        config
+        Ok(string)
+    }
+
+    /// Find the placeholders which are enclosed by another placeholder. Every placeholder is
+    /// replaced by a sentinel name, where a placeholder is nested if the pattern matches around
+    /// its sentinel.
+    fn nested_placeholders(&self, string: &str, text: bool) -> Vec<(Range<usize>, String)> {
+        const SENTINEL: &str = "0";
+
+        let mut probe = String::with_capacity(string.len());
+        let mut placeholders = Vec::new();
+        let mut last = 0;
+        for captures in self.placeholders(string, text) {
+            let whole = captures.get(0).unwrap();
+            probe.push_str(&string[last..whole.start()]);
+            placeholders.push((probe.len(), whole.range(), captures[1].to_string()));
+            probe.push_str(SENTINEL);
+            last = whole.end();
+        }
+        if placeholders.is_empty() {
+            return Vec::new();
+        }
+        probe.push_str(&string[last..]);
+
+        let enclosing: Vec<Range<usize>> = self.placeholders(&probe, text)
+            .map(|captures| captures.get(0).unwrap().range())
+            .filter(|found| found.len() > SENTINEL.len())
+            .collect();
+
+        placeholders.into_iter()
+            .filter(|(sentinel, _, _)| enclosing.iter().any(|found| found.start < *sentinel && *sentinel + SENTINEL.len() <= found.end))
+            .map(|(_, range, name)| (range, name))
+            .collect()
+    }
+
+    /// Apply the body of the macro, where the arguments are available through the names of the
+    /// parameters
+    fn call(&self, name: &str, arguments: Vec<Value>) -> Result<Value, super::Error> {
+        if self.calls.iter().any(|call| call == name) {
+            return Err(super::Error::RecursiveMacro(name.to_string()));
+        }
+
+        let (parameters, body) = match self.find_macro(name) {
+            Err(super::Error::UndefinedMacro(_)) if let Some(result) = self.functions.call(name, &arguments) => return result,
+            found => found?
+        };
+        if parameters.len() != arguments.len() {
+            return Err(super::Error::MacroArity(name.to_string(), parameters.len(), arguments.len()));
+        }
+
+        let mut scope = self.with_locals(parameters.into_iter().zip(arguments).collect());
+        scope.calls.push(name.to_string());
+        scope.apply_value(body.clone(), false)
+    }
+
+    /// Find the parameters and body of the macro with the signature `name(parameter, ...)`
+    fn find_macro(&self, name: &str) -> Result<(Vec<String>, &Value), super::Error> {
+        let macros = self.variables.get(MACROS_KEY).and_then(Value::as_object).into_iter().flatten();
+        for (signature, body) in macros {
+            let invalid = || super::Error::InvalidMacro(signature.to_string());
+            let (macro_name, parameters) = signature.split_once('(').ok_or_else(invalid)?;
+            if macro_name.trim() != name {
+                continue;
+            }
+
+            let parameters = parameters.trim_end().strip_suffix(')').ok_or_else(invalid)?;
+            let parameters: Vec<String> = match parameters.trim() {
+                "" => Vec::new(),
+                parameters => parameters.split(',').map(|parameter| parameter.trim().to_string()).collect()
+            };
+            if parameters.iter().any(|parameter| parameter.is_empty() || !parameter.chars().all(|char| char.is_alphanumeric() || char == '_')) {
+                return Err(invalid());
+            }
+
+            return Ok((parameters, body));
+        }
+
+        Err(super::Error::UndefinedMacro(name.to_string()))
+    }
+
+    fn lookup(&self, variable: &str) -> Result<Value, super::Error> {
+        if let Some(value) = files::resolve(variable, self.base_dir.as_deref(), &self.file_access) {
+            return value;
+        }
+
+        if let Some(value) = commands::resolve(variable, self.base_dir.as_deref(), &self.commands) {
+            return value;
+        }
+
+        if expression::is_expression(variable) {
+            return expression::evaluate(variable, |variable| self.lookup(variable), |name, arguments| self.call(name, arguments));
+        }
+
+        let value = match path::is_selector(variable) {
+            true => path::select_scoped(&self.locals, &self.variables, variable)?,
+            false => match self.local(variable) {
+                Some(value) => value.cloned(),
+                None => self.tree.get_variable_value(variable)
+            }
+        };
+        value.ok_or_else(|| super::Error::UndefinedVariable(variable.to_string()))
     }
 }
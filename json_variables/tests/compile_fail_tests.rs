@@ -0,0 +1,8 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    pub fn compile_fail() {
+        let t = trybuild::TestCases::new();
+        t.compile_fail("tests/ui/*.rs");
+    }
+}
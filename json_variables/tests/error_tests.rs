@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::{Error, JsonVariables};
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Person {
+        name: String,
+        age: u8
+    }
+
+    #[test]
+    pub fn parse_undefined_variable() {
+        let json = json!(
+        {
+            "variables": { "NAME": "John" },
+            "person": { "name": "${SURNAME}", "age": 23 }
+        });
+
+        let person: PersonVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let result = person.parse();
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "SURNAME"));
+    }
+
+    #[test]
+    pub fn parse_serde_error() {
+        let json = json!(
+        {
+            "variables": { "AGE": "twenty three" },
+            "person": { "name": "John", "age": "${AGE}" }
+        });
+
+        let person: PersonVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let err = person.parse().expect_err("A string age should not deserialize");
+
+        assert!(matches!(err, Error::Serde(_)));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    pub fn invalid_pattern() {
+        let err = json_variables::Variables::new(Default::default(), Some("${(".to_string()))
+            .expect_err("Pattern should not compile");
+
+        assert!(matches!(err, Error::InvalidPattern(ref pattern, _) if pattern == "${("));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    pub fn try_replace_undefined_variable() {
+        let variables = json_variables::from_str(r#"{ "NAME": "John" }"#).expect("Unable to parse string to variables");
+        let result = variables.try_replace(r#"{ "name": "${NAME}", "age": "${AGE}" }"#);
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "AGE"));
+    }
+}
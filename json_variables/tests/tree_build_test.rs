@@ -91,22 +91,22 @@ mod tests {
 
         dbg!(&tree);
 
-        let result = tree.get_variable_value(&"1".to_string());
+        let result = tree.get_variable_value("1");
         assert_eq!(result, Some(json!({ "2": "3" })));
 
-        let result = tree.get_variable_value(&"4".to_string());
+        let result = tree.get_variable_value("4");
         assert_eq!(result, Some(json!({ "5": "6" })));
 
-        let result = tree.get_variable_value(&"7".to_string());
+        let result = tree.get_variable_value("7");
         assert_eq!(result, Some(json!({ "8": "9" })));
 
-        let result = tree.get_variable_value(&"1.2".to_string());
+        let result = tree.get_variable_value("1.2");
         assert_eq!(result, Some(json!("3")));
 
-        let result = tree.get_variable_value(&"4.5".to_string());
+        let result = tree.get_variable_value("4.5");
         assert_eq!(result, Some(json!("6")));
 
-        let result = tree.get_variable_value(&"7.8".to_string());
+        let result = tree.get_variable_value("7.8");
         assert_eq!(result, Some(json!("9")));
     }
 
@@ -129,13 +129,36 @@ mod tests {
         let _ = tree.add_child(sub_3, "8".to_string(), json!("9"));
 
 
-        let result = tree.get_variable_value(&"2".to_string());
+        let result = tree.get_variable_value("2");
         assert_eq!(result, None);
 
-        let result = tree.get_variable_value(&"5".to_string());
+        let result = tree.get_variable_value("5");
         assert_eq!(result, None);
 
-        let result = tree.get_variable_value(&"8".to_string());
+        let result = tree.get_variable_value("8");
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn tree_get_value_same_name() {
+        let root = json!({
+            "port": 1,
+            "srv": { "port": 80 }
+        });
+
+        let mut tree = VariableTree::new(root);
+
+        let _ = tree.add_child(0, "port".to_string(), json!(1));
+        let sub = tree.add_child(0, "srv".to_string(), json!({ "port": 80 }));
+        let _ = tree.add_child(sub, "port".to_string(), json!(80));
+
+        let result = tree.get_variable_value("srv.port");
+        assert_eq!(result, Some(json!(80)));
+
+        let result = tree.get_variable_value("port");
+        assert_eq!(result, Some(json!(1)));
+
+        let result = tree.get_variable_value("port.srv");
         assert_eq!(result, None);
     }
 }
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use json_variables::JsonVariables;
+
+#[derive(Debug, Deserialize, Serialize, JsonVariables)]
+enum Person {
+    Name(String),
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+mod config {
+    use serde::{Deserialize, Serialize};
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Person {
+        name: String,
+    }
+}
+
+fn main() {
+    let _: Option<config::PersonVariables> = None;
+}
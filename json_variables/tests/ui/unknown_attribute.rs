@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use json_variables::JsonVariables;
+
+#[derive(Debug, Deserialize, Serialize, JsonVariables)]
+#[variables(data_name = "person")]
+struct Person {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use json_variables::Error;
+
+    #[rstest]
+    #[case(json!({ "NAME": "someone" }), json!({ "name": "${NAME}" }), json!({ "name": "someone" }))]
+    #[case(json!({ "AGE": 23 }), json!({ "age": "${AGE}" }), json!({ "age": 23 }))]
+    #[case(json!({ "AGE": 23 }), json!({ "age": "age is ${AGE}" }), json!({ "age": "age is 23" }))]
+    #[case(json!({ "NAMES": ["a", "b"] }), json!(["${NAMES.1}", "${NAMES}"]), json!(["b", ["a", "b"]]))]
+    #[case(json!({ "KEY": "name" }), json!({ "${KEY}": "someone" }), json!({ "name": "someone" }))]
+    #[case(json!({ "QUOTE": "say \"hi\"" }), json!({ "text": "${QUOTE}!" }), json!({ "text": "say \"hi\"!" }))]
+    #[case(json!({ "A": 1, "B": "x" }), json!("${A}${B}"), json!("1x"))]
+    pub fn apply(#[case] var: Value, #[case] data: Value, #[case] expected: Value) {
+        let variables = json_variables::from_json(var).expect("Unable to parse json to variables");
+        let result = variables.apply(data).expect("Unable to apply variables");
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    pub fn apply_undefined_variable() {
+        let variables = json_variables::from_json(json!({ "NAME": "someone" })).expect("Unable to parse json to variables");
+        let result = variables.apply(json!({ "names": ["${NAME}", "${OTHER}"] }));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "OTHER"));
+    }
+}
@@ -27,4 +27,38 @@ pub mod pattern {
         assert_eq!(x.name, "John");
         assert_eq!(x.age, 23);
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn pattern_attribute() {
+        #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+        #[variables(pattern = r"\{\{([a-zA-Z0-9_.]+)\}\}")]
+        struct Server {
+            host: String,
+            label: String
+        }
+
+        let json = json!(
+        {
+            "variables": { "HOST": "localhost" },
+            "server": { "host": "{{HOST}}", "label": "${HOST}" }
+        });
+
+        let server: ServerVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let server = server.parse().expect("Unable to set variables");
+        assert_eq!(server.host, "localhost");
+        assert_eq!(server.label, "${HOST}");
+    }
+
+    #[test]
+    pub fn invalid_pattern_attribute() {
+        #[allow(dead_code)]
+        #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+        #[variables(pattern = r"\{\{(")]
+        struct Server {
+            host: String
+        }
+
+        let result = serde_json::from_value::<ServerVariables>(json!({ "server": { "host": "localhost" } }));
+        assert!(result.is_err_and(|err| err.to_string().contains("Invalid regex pattern")));
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{JsonVariables, Variables};
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    enum Mode {
+        Fast,
+        Limited(u8)
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(coerce)]
+    struct Server {
+        port: u16,
+        debug: bool,
+        ratio: f64,
+        name: String,
+        label: String,
+        workers: Option<u32>,
+        ports: Vec<u16>,
+        mode: Mode,
+        limited: Mode
+    }
+
+    fn coerced(variables: Value) -> Variables {
+        let mut variables = json_variables::from_json(variables).expect("Unable to parse json to variables");
+        variables.coerce = true;
+        variables
+    }
+
+    #[test]
+    pub fn derive_coerce() {
+        let json = json!(
+        {
+            "variables": { "PORT": "8080", "DEBUG": "true", "RATIO": "0.5", "NAME": "1234", "WORKERS": "4", "MODE": "Fast", "LIMIT": "3" },
+            "server": {
+                "port": "${PORT}",
+                "debug": "${DEBUG}",
+                "ratio": "${RATIO}",
+                "name": "${NAME}",
+                "label": "port ${PORT}",
+                "workers": "${WORKERS}",
+                "ports": ["${PORT}", "1${PORT}"],
+                "mode": "${MODE}",
+                "limited": { "Limited": "${LIMIT}" }
+            }
+        });
+
+        let server: ServerVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let server = server.parse().expect("Unable to set variables");
+
+        assert_eq!(server.port, 8080);
+        assert!(server.debug);
+        assert_eq!(server.ratio, 0.5);
+        assert_eq!(server.name, "1234");
+        assert_eq!(server.label, "port 8080");
+        assert_eq!(server.workers, Some(4));
+        assert_eq!(server.ports, vec![8080, 18080]);
+        assert_eq!(server.mode, Mode::Fast);
+        assert_eq!(server.limited, Mode::Limited(3));
+    }
+
+    /// Strings written in the data are never coerced
+    #[test]
+    pub fn literal_strings_untouched() {
+        let variables = coerced(json!({}));
+        let result = variables.apply_into::<u16>(json!("8080"));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn coercion_disabled() {
+        let variables = json_variables::from_json(json!({ "PORT": "8080" })).expect("Unable to parse json to variables");
+        let result = variables.apply_into::<u16>(json!("${PORT}"));
+
+        assert!(result.is_err());
+    }
+
+    #[rstest]
+    #[case(json!({ "PORT": "70000" }), json!("${PORT}"))]
+    #[case(json!({ "PORT": "http" }), json!("${PORT}"))]
+    #[case(json!({ "PORT": "-1" }), json!("${PORT}"))]
+    pub fn invalid_coercion(#[case] variables: Value, #[case] data: Value) {
+        let variables = coerced(variables);
+        let result = variables.apply_into::<u16>(data);
+
+        assert!(matches!(result, Err(json_variables::Error::Serde(_))));
+    }
+
+    #[rstest]
+    #[case(json!({ "V": "true" }), json!("${V}"), json!(true))]
+    #[case(json!({ "V": "false" }), json!("${V}"), json!(false))]
+    #[case(json!({ "V": true }), json!("${V}"), json!(true))]
+    pub fn coerce_bool(#[case] variables: Value, #[case] data: Value, #[case] expected: Value) {
+        let variables = coerced(variables);
+        let result: bool = variables.apply_into(data).expect("Unable to coerce");
+
+        assert_eq!(Value::Bool(result), expected);
+    }
+
+    #[test]
+    pub fn coerce_keeps_strings_for_values() {
+        let variables = coerced(json!({ "PORT": "8080" }));
+        let result: Value = variables.apply_into(json!({ "port": "${PORT}" })).expect("Unable to apply variables");
+
+        assert_eq!(result, json!({ "port": "8080" }));
+    }
+}
@@ -0,0 +1,121 @@
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use json_variables::{CommandPolicy, Error, Variables};
+
+    fn variables(policy: CommandPolicy) -> Variables {
+        Variables::builder()
+            .variable("VERSION", "1.2.0")
+            .commands(policy)
+            .build()
+            .expect("Unable to build variables")
+    }
+
+    fn allowed() -> CommandPolicy {
+        CommandPolicy::enabled().allow("echo").allow("printf").allow("sh").allow("sleep")
+    }
+
+    #[rstest]
+    #[case("${cmd:echo abc1234}", json!("abc1234"))]
+    #[case("${cmd:printf '  padded  \n\n'}", json!("padded"))]
+    #[case("${cmd:echo 'two  words' \"and quotes\"}", json!("two  words and quotes"))]
+    #[case("${VERSION}+${cmd:echo abc1234}", json!("1.2.0+abc1234"))]
+    #[case("${cmd:echo $HOME;}", json!("$HOME;"))]
+    #[case("${cmd:printf ''}", json!(""))]
+    pub fn run(#[case] placeholder: &str, #[case] expected: Value) {
+        assert_eq!(variables(allowed()).apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[rstest]
+    #[case(CommandPolicy::default(), "${cmd:echo abc1234}")]
+    #[case(CommandPolicy::enabled(), "${cmd:echo abc1234}")]
+    #[case(allowed(), "${cmd:cat /etc/passwd}")]
+    #[case(allowed(), "${cmd:/bin/echo abc1234}")]
+    #[case(allowed(), "${cmd:}")]
+    #[case(allowed(), "${cmd:echo 'unterminated}")]
+    pub fn denied(#[case] policy: CommandPolicy, #[case] placeholder: &str) {
+        let result = variables(policy).apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::CommandDenied(..))));
+    }
+
+    #[rstest]
+    #[case("${cmd:echo ${BRANCH}}")]
+    #[case("${cmd:echo '${BRANCH}'}")]
+    #[case("${cmd:echo ${VERSION}}")]
+    #[case("${cmd:echo ${LOG_${LEVEL}}}")]
+    #[case("${${PREFIX}echo ${BRANCH}}")]
+    pub fn nested(#[case] placeholder: &str) {
+        for policy in [allowed(), allowed().shell(true)] {
+            let variables = Variables::builder()
+                .variable("BRANCH", "main --output=/tmp/pwned")
+                .variable("VERSION", "1.2.0")
+                .variable("LEVEL", "DEBUG")
+                .variable("LOG_DEBUG", "debug; echo pwned")
+                .variable("PREFIX", "cmd:")
+                .commands(policy)
+                .build()
+                .expect("Unable to build variables");
+
+            let result = variables.apply(json!(placeholder));
+
+            assert!(matches!(result, Err(Error::CommandDenied(..))), "Unexpected result {result:?}");
+        }
+    }
+
+    #[test]
+    pub fn failed() {
+        let result = variables(allowed()).apply(json!("${cmd:sh -c 'echo broken >&2; exit 3'}"));
+
+        match result {
+            Err(Error::CommandFailed(command, status, stderr)) => {
+                assert_eq!(command, "sh -c 'echo broken >&2; exit 3'");
+                assert_eq!(status, Some(3));
+                assert_eq!(stderr, "broken");
+            }
+            result => panic!("Unexpected result {result:?}")
+        }
+    }
+
+    #[test]
+    pub fn missing_executable() {
+        let policy = CommandPolicy::enabled().allow("json-variables-missing-executable");
+
+        let result = variables(policy).apply(json!("${cmd:json-variables-missing-executable}"));
+
+        assert!(matches!(result, Err(Error::CommandFailed(_, None, _))));
+    }
+
+    #[test]
+    pub fn timeout() {
+        let start = Instant::now();
+        let result = variables(allowed().timeout(Duration::from_millis(100))).apply(json!("${cmd:sleep 5}"));
+
+        assert!(matches!(result, Err(Error::CommandTimeout(ref command, _)) if command == "sleep 5"));
+        assert!(start.elapsed() < Duration::from_secs(4));
+    }
+
+    #[test]
+    pub fn shell() {
+        let placeholder = json!("${cmd:echo one | tr a-z A-Z}");
+
+        let plain = variables(allowed()).apply(placeholder.clone()).expect("Unable to apply variables");
+        let shell = variables(allowed().shell(true)).apply(placeholder).expect("Unable to apply variables");
+
+        assert_eq!(plain, json!("one | tr a-z A-Z"));
+        assert_eq!(shell, json!("ONE"));
+    }
+
+    #[test]
+    pub fn base_dir() {
+        let mut variables = variables(CommandPolicy::enabled().allow("pwd"));
+        variables.base_dir = Some(std::env::temp_dir().canonicalize().unwrap());
+
+        let value = variables.apply(json!("${cmd:pwd}")).expect("Unable to apply variables");
+
+        assert_eq!(value, json!(std::env::temp_dir().canonicalize().unwrap().display().to_string()));
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables};
+
+    fn variables(env: &str) -> Variables {
+        json_variables::from_json(json!({ "ENV": env, "REPLICAS": 3, "TLS": true }))
+            .expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case("prod", json!({ "name": "api", "tls": { "cert": "/etc/tls/cert.pem" } }))]
+    #[case("dev", json!({ "name": "api" }))]
+    pub fn if_object(#[case] env: &str, #[case] expected: Value) {
+        let data = json!({
+            "name": "api",
+            "tls": { "$if": "${ENV == 'prod'}", "cert": "/etc/tls/cert.pem" }
+        });
+
+        assert_eq!(variables(env).apply(data).expect("Unable to apply variables"), expected);
+    }
+
+    #[rstest]
+    #[case("prod", json!({ "database": { "host": "db.example.com", "replicas": 3 } }))]
+    #[case("dev", json!({ "database": { "host": "localhost" } }))]
+    pub fn if_else(#[case] env: &str, #[case] expected: Value) {
+        let data = json!({
+            "database": {
+                "$if": "${ENV == 'prod'}",
+                "host": "db.example.com",
+                "replicas": "${REPLICAS}",
+                "$else": { "host": "localhost" }
+            }
+        });
+
+        assert_eq!(variables(env).apply(data).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn else_scalar() {
+        let data = json!({ "log": { "$if": false, "level": "debug", "$else": "${ENV}" } });
+
+        assert_eq!(variables("dev").apply(data).expect("Unable to apply variables"), json!({ "log": "dev" }));
+    }
+
+    #[rstest]
+    #[case("prod", json!(["web", "worker", "monitor"]))]
+    #[case("dev", json!(["web", "worker", "debugger"]))]
+    pub fn when_array(#[case] env: &str, #[case] expected: Value) {
+        let data = json!([
+            { "name": "web" },
+            { "$when": "${TLS}", "name": "worker" },
+            { "$when": "${ENV == 'prod'}", "name": "monitor" },
+            { "$when": "${ENV != 'prod'}", "name": "debugger" },
+            { "$when": false, "name": "disabled" },
+            { "$if": "${ENV == 'staging'}", "name": "staging" }
+        ]);
+
+        let result = variables(env).apply(data).expect("Unable to apply variables");
+        let names: Vec<&Value> = result.as_array().unwrap().iter().map(|service| &service["name"]).collect();
+        assert_eq!(Value::Array(names.into_iter().cloned().collect()), expected);
+    }
+
+    #[test]
+    pub fn nested_conditions() {
+        let data = json!({
+            "outer": {
+                "$if": true,
+                "inner": { "$if": "${ENV == 'prod'}", "value": 1 },
+                "list": [{ "$when": "${REPLICAS > 2}", "value": 2 }]
+            }
+        });
+
+        assert_eq!(variables("dev").apply(data).expect("Unable to apply variables"), json!({ "outer": { "list": [{ "value": 2 }] } }));
+    }
+
+    #[test]
+    pub fn excluded_root() {
+        let data = json!({ "$if": false, "value": 1 });
+
+        assert_eq!(variables("dev").apply(data).expect("Unable to apply variables"), Value::Null);
+    }
+
+    #[rstest]
+    #[case(json!({ "tls": { "$if": "${ENV}" } }))]
+    #[case(json!([{ "$when": 1 }]))]
+    pub fn invalid_condition(#[case] data: Value) {
+        let result = variables("prod").apply(data);
+
+        assert!(matches!(result, Err(Error::InvalidCondition(_))));
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Tls {
+        cert: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Service {
+        name: String,
+        tls: Option<Tls>,
+        sidecars: Vec<String>
+    }
+
+    #[rstest]
+    #[case("prod", true, vec!["proxy", "metrics"])]
+    #[case("dev", false, vec!["proxy"])]
+    pub fn derive_conditions(#[case] env: &str, #[case] tls: bool, #[case] sidecars: Vec<&str>) {
+        let json = json!({
+            "variables": { "ENV": env },
+            "service": {
+                "name": "api",
+                "tls": { "$if": "${ENV == 'prod'}", "cert": "/etc/tls/cert.pem" },
+                "sidecars": ["proxy", { "$when": "${ENV == 'prod'}", "$if": false, "$else": "metrics" }]
+            }
+        });
+
+        let service: ServiceVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let service = service.parse().expect("Unable to set variables");
+
+        assert_eq!(service.tls.is_some(), tls);
+        assert_eq!(service.sidecars, sidecars);
+    }
+}
@@ -61,6 +61,7 @@ mod variables_attributes {
     /// Will simply not compile if the struct does not exist
     #[test]
     pub fn variables_name() {
+        #[allow(dead_code)]
         #[derive(Debug, Deserialize, Serialize, JsonVariables)]
         #[variables(variables_name = PersonDataNewVariables)]
         struct PersonData {
@@ -68,7 +69,7 @@ mod variables_attributes {
             age: u8
         }
 
-        let _ = PersonDataNewVariables { variables: Default::default(), data: "".to_string() };
+        let _ = PersonDataNewVariables { variables: Default::default(), data: Default::default() };
     }
 
     #[test]
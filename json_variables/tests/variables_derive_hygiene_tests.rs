@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, serde::Deserialize, serde::Serialize, JsonVariables)]
+    struct Person {
+        name: String
+    }
+
+    #[derive(Debug, serde::Deserialize, serde::Serialize, JsonVariables)]
+    pub(crate) struct Country {
+        short: String
+    }
+
+    #[test]
+    pub fn multiple_derives_in_module() {
+        let person: PersonVariables = serde_json::from_str(r#"
+            {
+                "variables": { "NAME": "John" },
+                "person": { "name": "${NAME}" }
+            }"#).expect("Unable to parse string to person variable");
+        let person = person.parse().expect("Unable to create person");
+
+        let country: CountryVariables = serde_json::from_str(r#"
+            {
+                "variables": { "SHORT": "NL" },
+                "country": { "short": "${SHORT}" }
+            }"#).expect("Unable to parse string to country variable");
+        let country = country.parse().expect("Unable to create country");
+
+        assert_eq!(person.name, "John");
+        assert_eq!(country.short, "NL");
+    }
+
+    mod nested {
+        #[derive(Debug, serde::Deserialize, serde::Serialize, json_variables::JsonVariables)]
+        pub struct Object {
+            pub name: String
+        }
+    }
+
+    #[test]
+    pub fn generated_types_follow_visibility() {
+        let object: nested::ObjectVariables = serde_json::from_str(r#"
+            {
+                "variables": { "NAME": "John" },
+                "object": { "name": "${NAME}" }
+            }"#).expect("Unable to parse string to object variable");
+        let object = object.parse().expect("Unable to create object");
+
+        assert_eq!(object.name, "John");
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::{Error, JsonVariables};
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Database {
+        host: String,
+        port: u16
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(flatten_data_field)]
+    struct Cache {
+        host: String,
+        region: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Service {
+        name: String,
+        #[variables(nested)]
+        database: Database,
+        #[variables(nested)]
+        caches: Vec<Cache>,
+        #[variables(nested)]
+        backup: Option<Database>
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(pattern = r"\{\{([^{}]+)\}\}", coerce)]
+    struct Listener {
+        host: String,
+        port: u16
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Gateway {
+        name: String,
+        #[variables(nested)]
+        listener: Listener
+    }
+
+    #[test]
+    pub fn nested_attributes() {
+        let listener = json!(
+        {
+            "variables": { "P": "8080" },
+            "listener": { "host": "{{HOST}}", "port": "{{P}}" }
+        });
+        let json = json!(
+        {
+            "variables": { "NAME": "gateway", "HOST": "localhost" },
+            "gateway": { "name": "${NAME}", "listener": listener }
+        });
+
+        let gateway: GatewayVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let gateway = gateway.parse().expect("Unable to set variables");
+
+        assert_eq!(gateway.name, "gateway");
+        assert_eq!(gateway.listener.host, "localhost");
+        assert_eq!(gateway.listener.port, 8080);
+    }
+
+    #[test]
+    pub fn nested_scopes() {
+        let json = json!(
+        {
+            "variables": { "NAME": "api", "HOST": "outer", "REGION": "eu" },
+            "service": {
+                "name": "${NAME}",
+                "database": {
+                    "variables": { "HOST": "inner", "PORT": 5432 },
+                    "database": { "host": "${HOST}", "port": "${PORT}" }
+                },
+                "caches": [
+                    { "variables": { "HOST": "cache" }, "host": "${HOST}", "region": "${REGION}" },
+                    { "host": "${HOST}", "region": "${REGION}" }
+                ],
+                "backup": null
+            }
+        });
+
+        let service: ServiceVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let service = service.parse().expect("Unable to set variables");
+
+        assert_eq!(service.name, "api");
+        assert_eq!(service.database.host, "inner");
+        assert_eq!(service.database.port, 5432);
+        assert_eq!(service.caches[0].host, "cache");
+        assert_eq!(service.caches[0].region, "eu");
+        assert_eq!(service.caches[1].host, "outer");
+        assert!(service.backup.is_none());
+    }
+
+    /// Variables of a nested block are not visible to its parent or siblings
+    #[test]
+    pub fn nested_variables_do_not_leak() {
+        let json = json!(
+        {
+            "variables": { "NAME": "api" },
+            "service": {
+                "name": "${PORT}",
+                "database": {
+                    "variables": { "PORT": 5432 },
+                    "database": { "host": "db", "port": "${PORT}" }
+                },
+                "caches": [],
+                "backup": {
+                    "database": { "host": "db", "port": "${PORT}" }
+                }
+            }
+        });
+
+        let service: ServiceVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let result = service.parse();
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "PORT"));
+    }
+
+    #[test]
+    pub fn nested_component_unchanged() {
+        let database = json!(
+        {
+            "variables": { "HOST": "db", "PORT": 5432 },
+            "database": { "host": "${HOST}", "port": "${PORT}" }
+        });
+
+        let standalone: DatabaseVariables = serde_json::from_value(database.clone()).expect("Unable to parse json to variable");
+        let standalone = standalone.parse().expect("Unable to set variables");
+
+        let json = json!(
+        {
+            "service": {
+                "name": "api",
+                "database": database,
+                "caches": [],
+                "backup": null
+            }
+        });
+        let service: ServiceVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let service = service.parse().expect("Unable to set variables");
+
+        assert_eq!(service.database.host, standalone.host);
+        assert_eq!(service.database.port, standalone.port);
+    }
+
+    fn nested_hosts() -> serde_json::Value {
+        json!(
+        {
+            "variables": {
+                "HOST": "file-outer",
+                "$profiles": { "prod": { "HOST": "profile" } }
+            },
+            "service": {
+                "name": "api",
+                "database": {
+                    "variables": { "HOST": "file-inner", "PORT": 5432 },
+                    "database": { "host": "${HOST}", "port": "${PORT}" }
+                },
+                "caches": [{ "variables": { "HOST": "file-cache" }, "host": "${HOST}", "region": "eu" }],
+                "backup": null
+            }
+        })
+    }
+
+    #[test]
+    pub fn overrides_beat_nested_variables() {
+        let service: ServiceVariables = serde_json::from_value(nested_hosts()).expect("Unable to parse json to variable");
+        let overrides = json!({ "HOST": "override" });
+
+        let service = service.parse_with_map(overrides.as_object().unwrap().clone()).expect("Unable to set variables");
+
+        assert_eq!(service.database.host, "override");
+        assert_eq!(service.database.port, 5432);
+        assert_eq!(service.caches[0].host, "override");
+    }
+
+    #[test]
+    pub fn profile_beats_nested_variables() {
+        let service: ServiceVariables = serde_json::from_value(nested_hosts()).expect("Unable to parse json to variable");
+
+        let service = service.parse_profile("prod").expect("Unable to set variables");
+
+        assert_eq!(service.database.host, "profile");
+        assert_eq!(service.caches[0].host, "profile");
+    }
+
+    #[test]
+    pub fn nested_variables_without_overrides() {
+        let service: ServiceVariables = serde_json::from_value(nested_hosts()).expect("Unable to parse json to variable");
+
+        let service = service.parse().expect("Unable to set variables");
+
+        assert_eq!(service.database.host, "file-inner");
+        assert_eq!(service.caches[0].host, "file-cache");
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::{Error, JsonVariables};
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(require = ["DB_URL", "REGION"])]
+    struct Deployment {
+        name: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Service {
+        #[variables(nested)]
+        deployment: Deployment
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(require = ["1 + 1", "hostname()", "file:./Cargo.toml", "SERVERS[0]"])]
+    struct Evaluated {
+        name: String
+    }
+
+    #[test]
+    pub fn require_defined() {
+        let json = json!(
+        {
+            "variables": { "DB_URL": "localhost", "REGION": "eu" },
+            "deployment": { "name": "api" }
+        });
+
+        let deployment: DeploymentVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let deployment = deployment.parse().expect("Unable to set variables");
+        assert_eq!(deployment.name, "api");
+    }
+
+    #[test]
+    pub fn require_missing() {
+        let json = json!(
+        {
+            "variables": { "REGION": "eu" },
+            "deployment": { "name": "api" }
+        });
+
+        let deployment: DeploymentVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let err = deployment.parse().expect_err("DB_URL is not defined");
+
+        assert!(matches!(err, Error::MissingVariables(ref missing) if missing == &["DB_URL"]));
+        assert_eq!(err.to_string(), "Required variables DB_URL are not defined");
+    }
+
+    #[test]
+    pub fn require_all_missing() {
+        let json = json!({ "deployment": { "name": "api" } });
+
+        let deployment: DeploymentVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let err = deployment.parse().expect_err("No variables are defined");
+
+        assert_eq!(err.to_string(), "Required variables DB_URL, REGION are not defined");
+    }
+
+    #[test]
+    pub fn require_defined_by_overrides() {
+        let json = json!(
+        {
+            "variables": { "REGION": "eu" },
+            "deployment": { "name": "api" }
+        });
+
+        let deployment: DeploymentVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let overrides = json!({ "DB_URL": "localhost" });
+        let deployment = deployment.parse_with_map(overrides.as_object().unwrap().clone());
+        assert!(deployment.is_ok());
+    }
+
+    #[test]
+    pub fn require_defined_by_parent_scope() {
+        let json = json!(
+        {
+            "variables": { "DB_URL": "localhost" },
+            "service": {
+                "deployment": {
+                    "variables": { "REGION": "eu" },
+                    "deployment": { "name": "api" }
+                }
+            }
+        });
+
+        let service: ServiceVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        assert!(service.parse().is_ok());
+    }
+
+    #[test]
+    pub fn require_does_not_evaluate() {
+        let json = json!(
+        {
+            "variables": { "SERVERS": ["a"] },
+            "evaluated": { "name": "api" }
+        });
+
+        let evaluated: EvaluatedVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let err = evaluated.parse().expect_err("Only definitions are checked");
+
+        assert!(matches!(err, Error::MissingVariables(ref missing) if missing == &["1 + 1", "hostname()", "file:./Cargo.toml"]));
+    }
+}
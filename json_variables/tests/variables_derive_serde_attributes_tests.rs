@@ -0,0 +1,193 @@
+#[cfg(test)]
+mod deny_unknown_fields {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(deny_unknown_fields)]
+    struct Person {
+        name: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(deny_unknown_fields)]
+    #[variables(flatten_data_field)]
+    struct Country {
+        short: String
+    }
+
+    #[test]
+    pub fn known_fields() {
+        let json = json!({ "variables": { "NAME": "John" }, "person": { "name": "${NAME}" } });
+        let person: PersonVariables = serde_json::from_value(json).unwrap();
+        assert_eq!(person.parse().unwrap().name, "John");
+
+        let json = json!({ "variables": { "SHORT": "NL" }, "short": "${SHORT}" });
+        let country: CountryVariables = serde_json::from_value(json).unwrap();
+        assert_eq!(country.parse().unwrap().short, "NL");
+    }
+
+    #[test]
+    pub fn unknown_data_field() {
+        let json = json!({ "variables": { "NAME": "John" }, "person": { "name": "${NAME}", "age": 23 } });
+        let person: PersonVariables = serde_json::from_value(json).unwrap();
+        assert!(person.parse().unwrap_err().to_string().contains("unknown field `age`"));
+
+        let json = json!({ "variables": { "SHORT": "NL" }, "short": "${SHORT}", "long": "Netherlands" });
+        let country: CountryVariables = serde_json::from_value(json).unwrap();
+        assert!(country.parse().unwrap_err().to_string().contains("unknown field `long`"));
+    }
+
+    #[test]
+    pub fn unknown_top_level_field() {
+        let json = json!({ "variables": { "NAME": "John" }, "person": { "name": "${NAME}" }, "other": 1 });
+        let person: PersonVariables = serde_json::from_value(json).unwrap();
+        assert!(person.parse().unwrap_err().to_string().contains("unknown field `other`"));
+    }
+}
+
+#[cfg(test)]
+mod rename_all {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(rename_all = "camelCase")]
+    struct Database {
+        host_name: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(rename_all = "kebab-case")]
+    struct Service {
+        service_name: String,
+        #[variables(nested)]
+        main_database: Database,
+        #[serde(rename = "backup")]
+        #[variables(nested)]
+        backup_database: Database
+    }
+
+    #[test]
+    pub fn rename_all() {
+        let json = json!(
+        {
+            "variables": { "NAME": "api", "HOST": "outer" },
+            "service": {
+                "service-name": "${NAME}",
+                "main-database": {
+                    "variables": { "HOST": "inner" },
+                    "database": { "hostName": "${HOST}" }
+                },
+                "backup": {
+                    "database": { "hostName": "${HOST}" }
+                }
+            }
+        });
+
+        let service: ServiceVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let service = service.parse().expect("Unable to set variables");
+
+        assert_eq!(service.service_name, "api");
+        assert_eq!(service.main_database.host_name, "inner");
+        assert_eq!(service.backup_database.host_name, "outer");
+    }
+
+    #[test]
+    pub fn rename_all_round_trip() {
+        let database = Database { host_name: "db".to_string() };
+        let database = DatabaseVariables::from_parts(&database, Default::default()).unwrap();
+
+        assert_eq!(serde_json::to_value(&database).unwrap(), json!({ "variables": {}, "database": { "hostName": "db" } }));
+        assert_eq!(database.parse().unwrap().host_name, "db");
+    }
+}
+
+#[cfg(test)]
+mod default {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Default, Deserialize, Serialize, JsonVariables)]
+    #[serde(default)]
+    struct Server {
+        host: String,
+        port: u16
+    }
+
+    fn default_client() -> Client {
+        Client { retries: 3 }
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(default = "default_client")]
+    struct Client {
+        retries: u8
+    }
+
+    #[test]
+    pub fn default_fields() {
+        let json = json!({ "variables": { "HOST": "localhost" }, "server": { "host": "${HOST}" } });
+        let server: ServerVariables = serde_json::from_value(json).unwrap();
+        let server = server.parse().unwrap();
+
+        assert_eq!(server.host, "localhost");
+        assert_eq!(server.port, 0);
+    }
+
+    #[test]
+    pub fn default_data() {
+        let server: ServerVariables = serde_json::from_value(json!({ "variables": {} })).unwrap();
+        let server = server.parse().unwrap();
+        assert_eq!(server.host, "");
+
+        let client: ClientVariables = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(client.parse().unwrap().retries, 3);
+
+        let client: ClientVariables = serde_json::from_value(json!({ "variables": { "RETRIES": 5 }, "client": { "retries": "${RETRIES}" } })).unwrap();
+        assert_eq!(client.parse().unwrap().retries, 5);
+    }
+}
+
+#[cfg(test)]
+mod tag {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(tag = "kind")]
+    struct Person {
+        name: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[serde(tag = "kind")]
+    #[variables(flatten_data_field)]
+    struct Country {
+        short: String
+    }
+
+    #[test]
+    pub fn tag() {
+        let json = json!({ "variables": { "NAME": "John" }, "person": { "kind": "Person", "name": "${NAME}" } });
+        let person: PersonVariables = serde_json::from_value(json).unwrap();
+        assert_eq!(person.parse().unwrap().name, "John");
+
+        let json = json!({ "variables": { "SHORT": "NL" }, "kind": "Country", "short": "${SHORT}" });
+        let country: CountryVariables = serde_json::from_value(json).unwrap();
+        assert_eq!(country.parse().unwrap().short, "NL");
+    }
+
+    #[test]
+    pub fn tag_round_trip() {
+        let country = Country { short: "NL".to_string() };
+        let country = CountryVariables::from_parts(&country, Default::default()).unwrap();
+
+        assert_eq!(serde_json::to_value(&country).unwrap(), json!({ "variables": {}, "kind": "Country", "short": "NL" }));
+        assert_eq!(country.parse().unwrap().short, "NL");
+    }
+}
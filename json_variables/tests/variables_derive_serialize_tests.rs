@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Person {
+        name: String,
+        age: u8
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(flatten_data_field)]
+    struct Country {
+        short: String,
+        long: String
+    }
+
+    #[test]
+    pub fn serialize_round_trip() {
+        let json = json!(
+        {
+            "variables": { "NAME": "John", "AGE": 23 },
+            "person": { "name": "${NAME}", "age": "${AGE}" }
+        });
+
+        let person: PersonVariables = serde_json::from_value(json.clone()).expect("Unable to parse json to variable");
+        let result = serde_json::to_value(&person).expect("Unable to serialize variable");
+
+        assert_eq!(result, json);
+    }
+
+    #[test]
+    pub fn serialize_round_trip_pattern() {
+        let json = json!(
+        {
+            "variables": { "NAME": "John", "pattern": "\\?\\[([a-zA-Z0-9_.]+)\\]" },
+            "person": { "name": "?[NAME]", "age": 23 }
+        });
+
+        let person: PersonVariables = serde_json::from_value(json.clone()).expect("Unable to parse json to variable");
+        let result = serde_json::to_value(&person).expect("Unable to serialize variable");
+
+        assert_eq!(result, json);
+    }
+
+    #[test]
+    pub fn serialize_flatten_round_trip() {
+        let json = json!(
+        {
+            "variables": { "SHORT": "NL" },
+            "short": "${SHORT}",
+            "long": "Netherlands"
+        });
+
+        let country: CountryVariables = serde_json::from_value(json.clone()).expect("Unable to parse json to variable");
+        let result = serde_json::to_value(&country).expect("Unable to serialize variable");
+
+        assert_eq!(result, json);
+    }
+
+    #[test]
+    pub fn accessors() {
+        let json = json!(
+        {
+            "variables": { "NAME": "John" },
+            "person": { "name": "${NAME}", "age": 23 }
+        });
+
+        let person: PersonVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+
+        assert_eq!(person.variables().get::<String>("NAME").expect("Variable should be defined"), "John");
+        assert_eq!(Value::Object(person.data().clone()), json!({ "person": { "name": "${NAME}", "age": 23 } }));
+    }
+
+    #[test]
+    pub fn edit_and_save() {
+        let person = Person { name: "John".to_string(), age: 23 };
+        let variables = json_variables::from_json(json!({ "NAME": "John" })).expect("Unable to parse json to variables");
+
+        let mut person = PersonVariables::from_parts(&person, variables).expect("Unable to create variable");
+        person.data_mut()["person"]["name"] = json!("${NAME}");
+        person.variables_mut().insert("NAME", json!("Jane")).expect("Unable to insert variable");
+        let json = serde_json::to_value(&person).expect("Unable to serialize variable");
+
+        assert_eq!(json, json!(
+        {
+            "variables": { "NAME": "Jane" },
+            "person": { "name": "${NAME}", "age": 23 }
+        }));
+
+        let person: PersonVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        assert_eq!(person.parse().expect("Unable to set variables").name, "Jane");
+    }
+
+    #[test]
+    pub fn from_parts() {
+        let person = Person { name: "John".to_string(), age: 23 };
+        let variables = json_variables::from_json(json!({ "NAME": "John" })).expect("Unable to parse json to variables");
+
+        let person = PersonVariables::from_parts(&person, variables).expect("Unable to create variable");
+        let result = serde_json::to_value(&person).expect("Unable to serialize variable");
+
+        assert_eq!(result, json!(
+        {
+            "variables": { "NAME": "John" },
+            "person": { "name": "John", "age": 23 }
+        }));
+
+        let person = person.parse().expect("Unable to set variables");
+        assert_eq!(person.name, "John");
+        assert_eq!(person.age, 23);
+    }
+
+    #[test]
+    pub fn from_parts_flatten() {
+        let country = Country { short: "NL".to_string(), long: "Netherlands".to_string() };
+
+        let country = CountryVariables::from_parts(&country, Default::default()).expect("Unable to create variable");
+        let result = serde_json::to_value(&country).expect("Unable to serialize variable");
+
+        assert_eq!(result, json!(
+        {
+            "variables": {},
+            "short": "NL",
+            "long": "Netherlands"
+        }));
+    }
+}
@@ -18,7 +18,7 @@ mod tests {
                 "object": { "name": "${NAME}" }
             }"#;
 
-        let object: ObjectVariables = serde_json::from_str(&json).expect("Unable to parse string to object variable");
+        let object: ObjectVariables = serde_json::from_str(json).expect("Unable to parse string to object variable");
         let object = object.parse().expect("Unable to create object");
         assert_eq!(object.name, "John");
     }
@@ -45,7 +45,7 @@ mod tests {
                 "Object": { "name": "${NAME}" }
             }"#;
 
-        let object: ObjectVariables = serde_json::from_str(&json).expect("Unable to parse string to object variable");
+        let object: ObjectVariables = serde_json::from_str(json).expect("Unable to parse string to object variable");
         let _ = object.parse().expect("Unable to create object");
     }
 
@@ -55,7 +55,7 @@ mod tests {
             {
                 "object": { "name": "John" }
             }"#;
-        let object: ObjectVariables = serde_json::from_str(&json).expect("Unable to parse string to object variable");
+        let object: ObjectVariables = serde_json::from_str(json).expect("Unable to parse string to object variable");
         let object = object.parse().expect("Unable to create object");
         assert_eq!(object.name, "John");
     }
@@ -67,7 +67,7 @@ mod tests {
             {
                 "object": { "name": "${NAME}" }
             }"#;
-        let object: ObjectVariables = serde_json::from_str(&json).expect("Unable to parse string to object variable");
+        let object: ObjectVariables = serde_json::from_str(json).expect("Unable to parse string to object variable");
         let _ = object.parse().expect("Unable to create object");
     }
 }
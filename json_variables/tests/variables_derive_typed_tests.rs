@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::JsonVariables;
+
+    fn default_region() -> String {
+        "eu".to_string()
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(deny_unknown_fields)]
+    struct DeployVars {
+        #[serde(rename = "DB_URL")]
+        db_url: String,
+        #[serde(rename = "REGION", default = "default_region")]
+        region: String,
+        #[serde(rename = "PORT")]
+        port: u16
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(variables_type = DeployVars)]
+    struct Deployment {
+        database: String,
+        region: String,
+        port: u16
+    }
+
+    #[test]
+    pub fn typed_variables() {
+        let json = json!(
+        {
+            "variables": { "DB_URL": "localhost", "PORT": 8080 },
+            "deployment": { "database": "${DB_URL}", "region": "${REGION}", "port": "${PORT}" }
+        });
+
+        let deployment: DeploymentVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let deployment = deployment.parse().expect("Unable to set variables");
+
+        assert_eq!(deployment.database, "localhost");
+        assert_eq!(deployment.region, "eu");
+        assert_eq!(deployment.port, 8080);
+    }
+
+    #[test]
+    pub fn typed_variables_unknown_field() {
+        let json = json!(
+        {
+            "variables": { "DB_URL": "localhost", "PORT": 8080, "REGOIN": "us" },
+            "deployment": { "database": "${DB_URL}", "region": "${REGION}", "port": "${PORT}" }
+        });
+
+        let result = serde_json::from_value::<DeploymentVariables>(json);
+        assert!(result.unwrap_err().to_string().contains("unknown field `REGOIN`"));
+    }
+
+    #[test]
+    pub fn typed_variables_invalid_type() {
+        let json = json!(
+        {
+            "variables": { "DB_URL": "localhost", "PORT": "http" },
+            "deployment": { "database": "${DB_URL}", "region": "${REGION}", "port": "${PORT}" }
+        });
+
+        let result = serde_json::from_value::<DeploymentVariables>(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn typed_variables_missing_block() {
+        let json = json!(
+        {
+            "deployment": { "database": "localhost", "region": "eu", "port": 8080 }
+        });
+
+        let result = serde_json::from_value::<DeploymentVariables>(json);
+        assert!(result.unwrap_err().to_string().contains("missing field `DB_URL`"));
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod checks {
+    use super::tests::{Server, Tls};
+
+    pub fn validate_server(server: &Server) -> Result<(), String> {
+        if server.port < 1024 {
+            return Err(format!("Port {} is reserved", server.port));
+        }
+        Ok(())
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("Either a certificate or a self signed certificate has to be used")]
+    pub struct TlsError;
+
+    pub fn validate_tls(tls: &Tls) -> Result<(), TlsError> {
+        match (&tls.certificate, tls.self_signed) {
+            (Some(_), false) | (None, true) => Ok(()),
+            _ => Err(TlsError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::{Error, JsonVariables};
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(validate = crate::checks::validate_server)]
+    pub struct Server {
+        pub port: u16
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    #[variables(validate = crate::checks::validate_tls)]
+    pub struct Tls {
+        pub certificate: Option<String>,
+        pub self_signed: bool
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    pub struct Cluster {
+        #[variables(nested)]
+        pub primary: Server,
+        #[variables(nested)]
+        pub replicas: Vec<Server>,
+        #[variables(nested)]
+        pub tls: Option<Tls>
+    }
+
+    #[test]
+    pub fn validate_nested() {
+        let server = |port: u16| json!({ "server": { "port": port } });
+        let tls = json!({ "tls": { "certificate": null, "self_signed": false } });
+        let cluster = |primary, replicas, tls| json!({ "cluster": { "primary": primary, "replicas": replicas, "tls": tls } });
+
+        let parse = |json| serde_json::from_value::<ClusterVariables>(json).unwrap().parse();
+
+        assert!(parse(cluster(server(8080), json!([server(8081)]), json!(null))).is_ok());
+        assert!(matches!(parse(cluster(server(5), json!([]), json!(null))), Err(Error::Validation(_))));
+        assert!(matches!(parse(cluster(server(8080), json!([server(8081), server(22)]), json!(null))), Err(Error::Validation(_))));
+        assert!(matches!(parse(cluster(server(8080), json!([]), tls)), Err(Error::Validation(_))));
+    }
+
+    #[test]
+    pub fn validate_ok() {
+        let json = json!({ "variables": { "PORT": 8080 }, "server": { "port": "${PORT}" } });
+        let server: ServerVariables = serde_json::from_value(json).unwrap();
+
+        assert_eq!(server.parse().unwrap().port, 8080);
+    }
+
+    #[test]
+    pub fn validate_after_substitution() {
+        let json = json!({ "variables": { "PORT": 80 }, "server": { "port": "${PORT}" } });
+        let server: ServerVariables = serde_json::from_value(json).unwrap();
+        let err = server.parse().unwrap_err();
+
+        assert!(matches!(err, Error::Validation(_)));
+        assert_eq!(err.to_string(), "Validation failed: Port 80 is reserved");
+    }
+
+    #[test]
+    pub fn validate_with_overrides() {
+        let json = json!({ "variables": { "PORT": 8080 }, "server": { "port": "${PORT}" } });
+        let server: ServerVariables = serde_json::from_value(json).unwrap();
+        let overrides = json!({ "PORT": 22 });
+
+        let result = server.parse_with_map(overrides.as_object().unwrap().clone());
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    #[test]
+    pub fn validate_error_source() {
+        let json = json!({ "variables": { "CERT": "ca.pem" }, "tls": { "certificate": "${CERT}", "self_signed": true } });
+        let tls: TlsVariables = serde_json::from_value(json).unwrap();
+        let err = tls.parse().unwrap_err();
+
+        let source = err.source().expect("Validation error should have a source");
+        assert!(source.is::<crate::checks::TlsError>());
+    }
+}
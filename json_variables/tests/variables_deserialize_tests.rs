@@ -14,7 +14,7 @@ mod tests {
         let variables_1: Variables = serde_json::from_str(var).unwrap();
         let variables_2: Map<String, Value> = serde_json::from_str(var).unwrap();
 
-        assert_eq!(variables_1.variables, variables_2);
+        assert_eq!(variables_1.as_map(), &variables_2);
     }
 
     #[rstest]
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use json_variables::{Error, Variables};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({ "A": "x", "QUOTE": "say \"hi\"", "PORT": 8080 })).expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case(json!("echo $${HOME:-/tmp}"), json!("echo ${HOME:-/tmp}"))]
+    #[case(json!("$${A}"), json!("${A}"))]
+    #[case(json!("${A}-$${A}"), json!("x-${A}"))]
+    #[case(json!("$${A}${PORT}"), json!("${A}8080"))]
+    #[case(json!({ "$${A}": "$${PORT}" }), json!({ "${A}": "${PORT}" }))]
+    #[case(json!("a ${"), json!("a ${"))]
+    pub fn apply_escaped(#[case] data: Value, #[case] expected: Value) {
+        assert_eq!(variables().apply(data).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn apply_unescaped_shell_expansion() {
+        let result = variables().apply(json!("echo ${HOME:-/tmp}"));
+
+        assert!(matches!(result, Err(Error::Expression(ref expression, _)) if expression == "HOME:-/tmp"));
+    }
+
+    #[rstest]
+    #[case(r#"{"s": "a ${", "b": "${A}"}"#, r#"{"s": "a ${", "b": "x"}"#)]
+    #[case("{\"s\": \"a ${\n\", \"b\": \"${A}\"}", "{\"s\": \"a ${\n\", \"b\": \"x\"}")]
+    #[case(r#"{"s": "$${A}", "b": "${A}"}"#, r#"{"s": "${A}", "b": "x"}"#)]
+    #[case(r#"{"s": "echo $${HOME:-/tmp}"}"#, r#"{"s": "echo ${HOME:-/tmp}"}"#)]
+    #[case(r#"{"s": "${QUOTE}!", "b": "${QUOTE}"}"#, r#"{"s": "say \"hi\"!", "b": "say \"hi\""}"#)]
+    #[case(r#"{"port": "${PORT}", "url": "localhost:${PORT}"}"#, r#"{"port": 8080, "url": "localhost:8080"}"#)]
+    pub fn replace_text(#[case] data: &str, #[case] expected: &str) {
+        assert_eq!(variables().try_replace(data).expect("Unable to replace variables"), expected);
+    }
+
+    #[test]
+    pub fn replace_text_is_valid_json() {
+        let replaced = variables().try_replace(r#"{"s": "${QUOTE}!"}"#).expect("Unable to replace variables");
+
+        let value: Value = serde_json::from_str(&replaced).expect("Replaced text should be valid json");
+        assert_eq!(value, json!({ "s": "say \"hi\"!" }));
+    }
+}
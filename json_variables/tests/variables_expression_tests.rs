@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({
+            "BASE_PORT": 8000,
+            "WORKERS": 4,
+            "MEMORY_MB": 1024,
+            "RATIO": 0.5,
+            "NAME": "api",
+            "DOMAIN": "example.com",
+            "DEBUG": false,
+            "ENV": "prod",
+            "PORT": "8080",
+            "SERVERS": [{ "port": 80 }, { "port": 443 }]
+        }))
+        .expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case("${BASE_PORT + 1}", json!(8001))]
+    #[case("${WORKERS * 2}", json!(8))]
+    #[case("${MEMORY_MB / 4}", json!(256))]
+    #[case("${MEMORY_MB / 3}", json!(341))]
+    #[case("${MEMORY_MB % 1000}", json!(24))]
+    #[case("${MEMORY_MB * RATIO}", json!(512.0))]
+    #[case("${7 / 2.0}", json!(3.5))]
+    #[case("${BASE_PORT + WORKERS * 2}", json!(8008))]
+    #[case("${(BASE_PORT + WORKERS) * 2}", json!(16008))]
+    #[case("${-WORKERS + 1}", json!(-3))]
+    #[case("${BASE_PORT - -1}", json!(8001))]
+    #[case("${NAME + '.' + DOMAIN}", json!("api.example.com"))]
+    #[case(r#"${NAME + "-" + ENV}"#, json!("api-prod"))]
+    #[case("${WORKERS > 2}", json!(true))]
+    #[case("${WORKERS <= 2}", json!(false))]
+    #[case("${RATIO < 1}", json!(true))]
+    #[case("${WORKERS == 4.0}", json!(true))]
+    #[case("${ENV == 'prod'}", json!(true))]
+    #[case("${ENV != 'prod'}", json!(false))]
+    #[case("${NAME < 'b'}", json!(true))]
+    #[case("${ENV == 'prod' && !DEBUG}", json!(true))]
+    #[case("${DEBUG || WORKERS > 8}", json!(false))]
+    #[case("${DEBUG && MISSING}", json!(false))]
+    #[case("${!DEBUG || MISSING}", json!(true))]
+    #[case("${DEBUG == null}", json!(false))]
+    #[case("${SERVERS[1].port + 1}", json!(444))]
+    #[case("${SERVERS[*].port == SERVERS[*].port}", json!(true))]
+    #[case("port ${BASE_PORT + 1}", json!("port 8001"))]
+    pub fn expression(#[case] placeholder: &str, #[case] expected: Value) {
+        assert_eq!(variables().apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[rstest]
+    #[case("${PORT + 1}")]
+    #[case("${NAME * 2}")]
+    #[case("${WORKERS && DEBUG}")]
+    #[case("${!WORKERS}")]
+    #[case("${-NAME}")]
+    #[case("${NAME < 1}")]
+    #[case("${WORKERS / 0}")]
+    #[case("${RATIO % 0}")]
+    #[case("${9223372036854775807 + 1}")]
+    #[case("${WORKERS +}")]
+    #[case("${(WORKERS + 1}")]
+    #[case("${WORKERS 1}")]
+    #[case("${'unterminated}")]
+    #[case("${WORKERS # 1}")]
+    pub fn invalid_expression(#[case] placeholder: &str) {
+        let expression = &placeholder[2..placeholder.len() - 1];
+        let err = variables().apply(json!(placeholder)).expect_err("Expression should not evaluate");
+
+        assert!(matches!(err, Error::Expression(ref text, _) if text == expression));
+        assert!(err.to_string().contains(expression));
+    }
+
+    #[test]
+    pub fn undefined_variable() {
+        let result = variables().apply(json!("${WORKERS + THREADS}"));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "THREADS"));
+    }
+
+    #[test]
+    pub fn expression_replace() {
+        let replaced = variables().replace(r#"{ "port": "${BASE_PORT + 1}", "url": "http://${NAME + \".\" + DOMAIN}" }"#);
+
+        assert_eq!(serde_json::from_str::<Value>(&replaced).unwrap(), json!({ "port": 8001, "url": "http://api.example.com" }));
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Server {
+        port: u16,
+        workers: u32,
+        debug: bool
+    }
+
+    #[test]
+    pub fn derive_expression() {
+        let json = json!({
+            "variables": { "BASE_PORT": 8000, "CPUS": 4, "ENV": "dev" },
+            "server": { "port": "${BASE_PORT + 80}", "workers": "${CPUS * 2}", "debug": "${ENV != 'prod'}" }
+        });
+
+        let server: ServerVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let server = server.parse().expect("Unable to set variables");
+
+        assert_eq!(server.port, 8080);
+        assert_eq!(server.workers, 8);
+        assert!(server.debug);
+    }
+}
@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::{Path, PathBuf}};
+
+    use rstest::rstest;
+    use serde_json::json;
+    use json_variables::{Error, FileAccessPolicy, Variables};
+
+    /// Directory with a `config` directory holding the templates and a `secrets` directory next
+    /// to it, unique per test such that tests can run in parallel
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("json_variables_file_access_{}_{name}", std::process::id()));
+        fs::create_dir_all(directory.join("config/tables")).expect("Unable to create directory");
+        fs::create_dir_all(directory.join("secrets")).expect("Unable to create directory");
+        fs::write(directory.join("config/ca.pem"), "certificate").unwrap();
+        fs::write(directory.join("config/tables/regions.json"), r#"{ "eu": "eu.example.com" }"#).unwrap();
+        fs::write(directory.join("secrets/password"), "hunter2").unwrap();
+        directory
+    }
+
+    fn variables(directory: &Path, policy: FileAccessPolicy) -> Variables {
+        let mut variables = Variables::builder()
+            .file_access(policy)
+            .build()
+            .expect("Unable to build variables");
+        variables.base_dir = Some(directory.join("config"));
+        variables
+    }
+
+    #[rstest]
+    #[case("${file:./ca.pem}", json!("certificate"))]
+    #[case("${json:./tables/regions.json#/eu}", json!("eu.example.com"))]
+    #[case("${json:./tables/../tables/regions.json#/eu}", json!("eu.example.com"))]
+    pub fn allowed(#[case] placeholder: &str, #[case] expected: serde_json::Value) {
+        let directory = directory("allowed");
+        let policy = FileAccessPolicy::default().allow_root(directory.join("config")).max_size(64);
+
+        assert_eq!(variables(&directory, policy).apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[rstest]
+    #[case("${file:../secrets/password}", "../secrets/password")]
+    #[case("${file:./tables/../../secrets/password}", "./tables/../../secrets/password")]
+    #[case("${json:../secrets/password}", "../secrets/password")]
+    pub fn outside_root(#[case] placeholder: &str, #[case] file: &str) {
+        let directory = directory("outside_root");
+        let policy = FileAccessPolicy::default().allow_root(directory.join("config"));
+
+        let result = variables(&directory, policy).apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::AccessDenied(ref path, _)) if path == file));
+    }
+
+    #[test]
+    pub fn absolute_path_outside_root() {
+        let directory = directory("absolute_path_outside_root");
+        let policy = FileAccessPolicy::default().allow_root(directory.join("config"));
+        let placeholder = format!("${{file:{}}}", directory.join("secrets/password").display());
+
+        let result = variables(&directory, policy).apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::AccessDenied(..))));
+    }
+
+    #[test]
+    pub fn several_roots() {
+        let directory = directory("several_roots");
+        let policy = FileAccessPolicy::default()
+            .allow_root(directory.join("config"))
+            .allow_root(directory.join("secrets"));
+
+        let value = variables(&directory, policy).apply(json!("${file:../secrets/password}")).expect("Unable to apply variables");
+
+        assert_eq!(value, json!("hunter2"));
+    }
+
+    #[test]
+    pub fn relative_root() {
+        let directory = directory("relative_root");
+        let policy = FileAccessPolicy::default().allow_root("./tables");
+
+        let variables = variables(&directory, policy);
+
+        assert_eq!(variables.apply(json!("${json:./tables/regions.json#/eu}")).unwrap(), json!("eu.example.com"));
+        assert!(matches!(variables.apply(json!("${file:./ca.pem}")), Err(Error::AccessDenied(..))));
+    }
+
+    #[test]
+    pub fn confined_to_base_dir_by_default() {
+        let directory = directory("confined_to_base_dir_by_default");
+        let absolute = format!("${{file:{}}}", directory.join("secrets/password").display());
+
+        let variables = variables(&directory, FileAccessPolicy::default());
+
+        assert_eq!(variables.apply(json!("${file:./ca.pem}")).unwrap(), json!("certificate"));
+        assert!(matches!(variables.apply(json!("${file:../secrets/password}")), Err(Error::AccessDenied(..))));
+        assert!(matches!(variables.apply(json!(absolute)), Err(Error::AccessDenied(..))));
+    }
+
+    #[test]
+    pub fn unrestricted() {
+        let directory = directory("unrestricted");
+
+        let value = variables(&directory, FileAccessPolicy::unrestricted()).apply(json!("${file:../secrets/password}")).expect("Unable to apply variables");
+
+        assert_eq!(value, json!("hunter2"));
+    }
+
+    #[rstest]
+    #[case("${file:./ca.pem}")]
+    #[case("${json:./tables/regions.json}")]
+    #[case("${file:./missing.pem}")]
+    pub fn disabled(#[case] placeholder: &str) {
+        let directory = directory("disabled");
+
+        let result = variables(&directory, FileAccessPolicy::disabled()).apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::AccessDenied(..))));
+    }
+
+    #[test]
+    pub fn max_size() {
+        let directory = directory("max_size");
+        let policy = FileAccessPolicy::unrestricted().max_size(8);
+
+        let variables = variables(&directory, policy);
+
+        assert!(matches!(variables.apply(json!("${file:./ca.pem}")), Err(Error::AccessDenied(ref path, _)) if path == "./ca.pem"));
+        assert_eq!(variables.apply(json!("${file:../secrets/password}")).unwrap(), json!("hunter2"));
+    }
+
+    #[test]
+    pub fn max_size_exact() {
+        let directory = directory("max_size_exact");
+        let policy = FileAccessPolicy::default().max_size(11);
+
+        let value = variables(&directory, policy).apply(json!("${file:./ca.pem}")).expect("Unable to apply variables");
+
+        assert_eq!(value, json!("certificate"));
+    }
+
+    #[test]
+    pub fn directory_denied() {
+        let directory = directory("directory_denied");
+        let policy = FileAccessPolicy::default().allow_root(directory.join("config"));
+
+        let result = variables(&directory, policy).apply(json!("${file:./tables}"));
+
+        assert!(matches!(result, Err(Error::AccessDenied(ref path, _)) if path == "./tables"));
+    }
+
+    #[test]
+    pub fn missing_file() {
+        let directory = directory("missing_file");
+        let policy = FileAccessPolicy::default().allow_root(directory.join("config"));
+
+        let result = variables(&directory, policy).apply(json!("${file:./missing.pem}"));
+
+        assert!(matches!(result, Err(Error::Io(ref path, _)) if path == "./missing.pem"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn fifo_denied() {
+        let directory = directory("fifo_denied");
+        let fifo = directory.join("config/fifo");
+        if !fifo.exists() {
+            let status = std::process::Command::new("mkfifo").arg(&fifo).status().expect("Unable to run mkfifo");
+            assert!(status.success());
+        }
+
+        let result = variables(&directory, FileAccessPolicy::default().max_size(64)).apply(json!("${file:./fifo}"));
+
+        assert!(matches!(result, Err(Error::AccessDenied(ref path, _)) if path == "./fifo"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn symlink_outside_root() {
+        let directory = directory("symlink_outside_root");
+        let _ = std::os::unix::fs::symlink(directory.join("secrets/password"), directory.join("config/password"));
+        let policy = FileAccessPolicy::default().allow_root(directory.join("config"));
+
+        let result = variables(&directory, policy).apply(json!("${file:./password}"));
+
+        assert!(matches!(result, Err(Error::AccessDenied(..))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn symlinks() {
+        let directory = directory("symlinks");
+        let _ = std::os::unix::fs::symlink(directory.join("config/tables"), directory.join("config/linked"));
+        let root = FileAccessPolicy::default().allow_root(directory.join("config"));
+
+        let followed = variables(&directory, root.clone()).apply(json!("${json:./linked/regions.json#/eu}"));
+        let denied = variables(&directory, root.follow_symlinks(false)).apply(json!("${json:./linked/regions.json#/eu}"));
+
+        assert_eq!(followed.expect("Unable to apply variables"), json!("eu.example.com"));
+        assert!(matches!(denied, Err(Error::AccessDenied(..))));
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables};
+
+    /// Directory with the files of a test, unique per test such that tests can run in parallel
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("json_variables_files_{}_{name}", std::process::id()));
+        fs::create_dir_all(directory.join("tables")).expect("Unable to create directory");
+        fs::write(directory.join("ca.pem"), "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n").unwrap();
+        fs::write(directory.join("query.sql"), "SELECT \"name\" FROM users").unwrap();
+        fs::write(directory.join("tables/regions.json"), r#"{ "eu": { "endpoint": "eu.example.com", "zones": [1, 2] }, "us": { "endpoint": "us.example.com" } }"#).unwrap();
+        fs::write(directory.join("tables/invalid.json"), "{ \"eu\": ").unwrap();
+        directory
+    }
+
+    fn variables(directory: PathBuf) -> Variables {
+        let mut variables = json_variables::from_json(json!({ "REGION": "eu" })).expect("Unable to parse json to variables");
+        variables.base_dir = Some(directory);
+        variables
+    }
+
+    #[rstest]
+    #[case("${file:./ca.pem}", json!("-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n"))]
+    #[case("${file:query.sql}", json!("SELECT \"name\" FROM users"))]
+    #[case("query: ${file:./query.sql}", json!("query: SELECT \"name\" FROM users"))]
+    #[case("${json:./tables/regions.json#/eu}", json!({ "endpoint": "eu.example.com", "zones": [1, 2] }))]
+    #[case("${json:./tables/regions.json#/eu/zones/1}", json!(2))]
+    #[case("${json:./tables/regions.json#/us/endpoint}", json!("us.example.com"))]
+    #[case("${json:./tables/regions.json#}", json!({ "eu": { "endpoint": "eu.example.com", "zones": [1, 2] }, "us": { "endpoint": "us.example.com" } }))]
+    pub fn inline_file(#[case] placeholder: &str, #[case] expected: Value) {
+        let variables = variables(directory("inline_file"));
+
+        assert_eq!(variables.apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn inline_json() {
+        let variables = variables(directory("inline_json"));
+
+        let value = variables.apply(json!({ "regions": "${json:./tables/regions.json}" })).expect("Unable to apply variables");
+
+        assert_eq!(value["regions"]["us"]["endpoint"], json!("us.example.com"));
+    }
+
+    #[test]
+    pub fn absolute_path() {
+        let directory = directory("absolute_path");
+        let placeholder = format!("${{file:{}}}", directory.join("query.sql").display());
+
+        let value = variables(directory).apply(json!(placeholder)).expect("Unable to apply variables");
+
+        assert_eq!(value, json!("SELECT \"name\" FROM users"));
+    }
+
+    #[rstest]
+    #[case("${file:./missing.pem}", "./missing.pem")]
+    #[case("${json:./tables/missing.json#/eu}", "./tables/missing.json")]
+    pub fn missing_file(#[case] placeholder: &str, #[case] file: &str) {
+        let result = variables(directory("missing_file")).apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::Io(ref path, _)) if path == file));
+    }
+
+    #[test]
+    pub fn invalid_json() {
+        let result = variables(directory("invalid_json")).apply(json!("${json:./tables/invalid.json}"));
+
+        assert!(matches!(result, Err(Error::InvalidFile(ref path, _)) if path == "./tables/invalid.json"));
+    }
+
+    #[test]
+    pub fn missing_pointer() {
+        let result = variables(directory("missing_pointer")).apply(json!("${json:./tables/regions.json#/ap}"));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref name)) if name == "json:./tables/regions.json#/ap"));
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Region {
+        endpoint: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Deployment {
+        region: Region,
+        ca: String
+    }
+
+    #[test]
+    pub fn derive_from_file() {
+        let directory = directory("derive_from_file");
+        let template = json!({
+            "variables": {},
+            "deployment": { "region": "${json:./tables/regions.json#/eu}", "ca": "${file:./ca.pem}" }
+        });
+        fs::write(directory.join("deployment.json"), template.to_string()).unwrap();
+
+        let deployment = DeploymentVariables::from_file(directory.join("deployment.json"))
+            .expect("Unable to read template")
+            .parse()
+            .expect("Unable to set variables");
+
+        assert_eq!(deployment.region.endpoint, "eu.example.com");
+        assert!(deployment.ca.starts_with("-----BEGIN CERTIFICATE-----"));
+    }
+
+    #[rstest]
+    #[case("${file:../ca.pem}", "../ca.pem")]
+    #[case("${file:/etc/passwd}", "/etc/passwd")]
+    pub fn derive_outside_template_directory(#[case] placeholder: &str, #[case] file: &str) {
+        let directory = directory("derive_outside_template_directory");
+        let template = json!({
+            "variables": {},
+            "deployment": { "region": { "endpoint": "eu.example.com" }, "ca": placeholder }
+        });
+        fs::write(directory.join("tables/deployment.json"), template.to_string()).unwrap();
+
+        let result = DeploymentVariables::from_file(directory.join("tables/deployment.json"))
+            .expect("Unable to read template")
+            .parse();
+
+        assert!(matches!(result, Err(Error::AccessDenied(ref path, _)) if path == file));
+    }
+
+    #[test]
+    pub fn derive_missing_template() {
+        let result = DeploymentVariables::from_file("./missing/deployment.json");
+
+        assert!(matches!(result, Err(Error::Io(..))));
+    }
+}
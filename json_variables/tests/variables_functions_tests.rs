@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests {
+    use std::{panic, sync::{atomic::{AtomicI64, Ordering}, Arc, Mutex}};
+
+    use chrono::{TimeZone, Utc};
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, Functions, JsonVariables, Variables};
+
+    fn variables(functions: Functions) -> Variables {
+        let mut variables = json_variables::from_json(json!({ "MIN": 5, "MAX": 6 })).expect("Unable to parse json to variables");
+        variables.functions = functions;
+        variables
+    }
+
+    fn fixed() -> Functions {
+        Functions::default()
+            .with_fixed_time(Utc.with_ymd_and_hms(2024, 1, 31, 12, 30, 0).unwrap())
+            .with_seed(42)
+            .with_hostname("build-agent")
+    }
+
+    #[rstest]
+    #[case("${now()}", json!("2024-01-31T12:30:00+00:00"))]
+    #[case("${now('%Y-%m-%d')}", json!("2024-01-31"))]
+    #[case("release-${now('%Y%m%d.%H%M')}", json!("release-20240131.1230"))]
+    #[case("${hostname()}", json!("build-agent"))]
+    #[case("${hostname() + ':' + now('%H')}", json!("build-agent:12"))]
+    #[case("${random_int(MIN, MAX) >= 5}", json!(true))]
+    #[case("${random_int(7, 7)}", json!(7))]
+    pub fn call(#[case] placeholder: &str, #[case] expected: Value) {
+        assert_eq!(variables(fixed()).apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn uuid_format() {
+        let value = variables(Functions::default()).apply(json!("${uuid()}")).expect("Unable to apply variables");
+        let uuid = value.as_str().unwrap();
+
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(uuid.chars().filter(|char| *char == '-').count(), 4);
+        assert_eq!(&uuid[14..15], "4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"));
+    }
+
+    #[test]
+    pub fn seeded_renders_are_stable() {
+        let data = json!({ "id": "${uuid()}", "other": "${uuid()}", "number": "${random_int(1, 1000000)}" });
+
+        let first = variables(fixed()).apply(data.clone()).expect("Unable to apply variables");
+        let variables = variables(fixed());
+        let second = variables.apply(data.clone()).expect("Unable to apply variables");
+        let third = variables.apply(data).expect("Unable to apply variables");
+
+        assert_eq!(first, second);
+        assert_eq!(second, third);
+        assert_ne!(first["id"], first["other"]);
+    }
+
+    #[test]
+    pub fn different_seeds() {
+        let first = variables(Functions::default().with_seed(1)).apply(json!("${uuid()}")).unwrap();
+        let second = variables(Functions::default().with_seed(2)).apply(json!("${uuid()}")).unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    pub fn unseeded_calls_differ() {
+        let variables = variables(Functions::default());
+        let first = variables.apply(json!("${uuid()}")).unwrap();
+        let second = variables.apply(json!("${uuid()}")).unwrap();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    pub fn memoize() {
+        let data = json!({ "id": "${uuid()}", "same": "${uuid()}", "number": "${random_int(1, 1000000)}", "copy": "${random_int(1, 1000000)}" });
+
+        let value = variables(Functions::default().memoize(true)).apply(data).expect("Unable to apply variables");
+        assert_eq!(value["id"], value["same"]);
+        assert_eq!(value["number"], value["copy"]);
+
+        let variables = variables(Functions::default().memoize(true));
+        let first = variables.apply(json!("${uuid()}")).unwrap();
+        let second = variables.apply(json!("${uuid()}")).unwrap();
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    pub fn clock() {
+        let seconds = Arc::new(AtomicI64::new(0));
+        let clock = seconds.clone();
+        let variables = variables(Functions::default().with_clock(move || {
+            Utc.timestamp_opt(clock.fetch_add(60, Ordering::SeqCst), 0).unwrap()
+        }));
+
+        let value = variables.apply(json!(["${now('%M')}", "${now('%M')}"])).expect("Unable to apply variables");
+
+        assert_eq!(value, json!(["00", "01"]));
+        assert_eq!(seconds.load(Ordering::SeqCst), 120);
+    }
+
+    #[rstest]
+    #[case("${now('%Q')}", "now")]
+    #[case("${now(1)}", "now")]
+    #[case("${uuid(1)}", "uuid")]
+    #[case("${random_int(10, 1)}", "random_int")]
+    #[case("${random_int('a', 1)}", "random_int")]
+    #[case("${hostname('x')}", "hostname")]
+    pub fn invalid_call(#[case] placeholder: &str, #[case] function: &str) {
+        let result = variables(fixed()).apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::Function(ref name, _)) if name == function));
+    }
+
+    #[test]
+    pub fn macros_shadow_functions() {
+        let mut variables = json_variables::from_json(json!({ "$macros": { "hostname()": "localhost" } })).unwrap();
+        variables.functions = fixed();
+
+        assert_eq!(variables.apply(json!("${hostname()}")).unwrap(), json!("localhost"));
+    }
+
+    #[test]
+    pub fn system_hostname() {
+        let value = variables(Functions::default()).apply(json!("${hostname()}")).expect("Unable to apply variables");
+
+        assert!(!value.as_str().unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn unwind_safe() {
+        let variables = variables(fixed());
+
+        let value = panic::catch_unwind(|| variables.apply(json!("${hostname()}")));
+
+        assert_eq!(value.expect("Render should not panic").expect("Unable to apply variables"), json!("build-agent"));
+    }
+
+    #[test]
+    pub fn clones_render_independently() {
+        // The clock renders the clone in the middle of a render of the original
+        let clone: Arc<Mutex<Option<Variables>>> = Arc::new(Mutex::new(None));
+        let slot = clone.clone();
+        let functions = fixed().memoize(true).with_clock(move || {
+            if let Some(clone) = slot.lock().unwrap().take() {
+                clone.apply(json!(["${uuid()}", "${random_int(1, 1000000)}"])).expect("Unable to apply variables");
+            }
+            Utc.with_ymd_and_hms(2024, 1, 31, 12, 30, 0).unwrap()
+        });
+        let variables = variables(functions);
+        *clone.lock().unwrap() = Some(variables.clone());
+
+        let value = variables.apply(json!(["${uuid()}", "${now()}", "${uuid()}"])).expect("Unable to apply variables");
+
+        assert!(clone.lock().unwrap().is_none());
+        assert_eq!(value[0], value[2]);
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Build {
+        id: String,
+        date: String
+    }
+
+    #[test]
+    pub fn derive_functions() {
+        let json = json!({
+            "variables": {},
+            "build": { "id": "${uuid()}", "date": "${now('%Y-%m-%d')}" }
+        });
+
+        let mut first: BuildVariables = serde_json::from_value(json.clone()).expect("Unable to parse json to variable");
+        first.variables_mut().functions = fixed();
+        let mut second: BuildVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        second.variables_mut().functions = fixed();
+
+        let first = first.parse().expect("Unable to set variables");
+        let second = second.parse().expect("Unable to set variables");
+
+        assert_eq!(first.date, "2024-01-31");
+        assert_eq!(first.id, second.id);
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use json_variables::{ArrayMerge, MergeStrategy, ObjectMerge, Variables, VariablesStack};
+
+    fn variables(json: Value) -> Variables {
+        json_variables::from_json(json).expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case(MergeStrategy::REPLACE, json!({ "DB": { "port": 5433, "tags": ["c"] }, "NAME": "John" }))]
+    #[case(MergeStrategy::DEEP, json!({ "DB": { "host": "localhost", "port": 5433, "tags": ["c"] }, "NAME": "John" }))]
+    #[case(MergeStrategy::DEEP.arrays(ArrayMerge::Append), json!({ "DB": { "host": "localhost", "port": 5433, "tags": ["a", "b", "c"] }, "NAME": "John" }))]
+    #[case(MergeStrategy { objects: ObjectMerge::Replace, arrays: ArrayMerge::Append }, json!({ "DB": { "port": 5433, "tags": ["c"] }, "NAME": "John" }))]
+    pub fn layer(#[case] strategy: MergeStrategy, #[case] expected: Value) {
+        let mut base = variables(json!({ "DB": { "host": "localhost", "port": 5432, "tags": ["a", "b"] }, "NAME": "John" }));
+        base.layer(variables(json!({ "DB": { "port": 5433, "tags": ["c"] } })), strategy);
+
+        assert_eq!(Value::Object(base.into_map()), expected);
+    }
+
+    #[test]
+    pub fn layer_rebuilds_tree() {
+        let mut base = variables(json!({ "TAGS": ["a"] }));
+        base.layer(variables(json!({ "TAGS": ["b"], "DB": { "url": "localhost" } })), MergeStrategy::REPLACE.arrays(ArrayMerge::Append));
+
+        assert!(base.contains("TAGS.1"));
+        assert!(base.contains("DB.url"));
+        assert_eq!(base.replace(r#""${TAGS.1}""#), r#""b""#);
+    }
+
+    #[test]
+    pub fn layer_keeps_pattern() {
+        let mut base = Variables::new(json!({ "NAME": "John" }).as_object().unwrap().clone(), Some(r"\{\{([a-zA-Z0-9_.]+)\}\}".to_string()))
+            .expect("Unable to create variables");
+        base.layer(variables(json!({ "NAME": "Jane" })), MergeStrategy::REPLACE);
+
+        assert_eq!(base.replace(r#""{{NAME}}""#), r#""Jane""#);
+    }
+
+    #[test]
+    pub fn stack_precedence() {
+        let mut stack = VariablesStack::new();
+        stack
+            .push(30, variables(json!({ "PORT": 9000 })), MergeStrategy::REPLACE)
+            .push(0, variables(json!({ "HOST": "localhost", "PORT": 8080, "DB": { "host": "localhost", "port": 5432 } })), MergeStrategy::REPLACE)
+            .push(20, variables(json!({ "DB": { "host": "db.example.com" } })), MergeStrategy::DEEP)
+            .push(10, variables(json!({ "HOST": "example.com", "PORT": 8081 })), MergeStrategy::REPLACE);
+
+        let variables = stack.resolve();
+
+        assert_eq!(variables.replace(r#""${HOST}:${PORT}""#), r#""example.com:9000""#);
+        assert_eq!(variables.replace(r#""${DB.host}:${DB.port}""#), r#""db.example.com:5432""#);
+    }
+
+    #[test]
+    pub fn stack_equal_precedence() {
+        let variables = VariablesStack::new()
+            .with_layer(0, variables(json!({ "NAME": "John" })), MergeStrategy::REPLACE)
+            .with_layer(0, variables(json!({ "NAME": "Jane" })), MergeStrategy::REPLACE)
+            .resolve();
+
+        assert_eq!(variables.replace(r#""${NAME}""#), r#""Jane""#);
+    }
+
+    #[test]
+    pub fn empty_stack() {
+        let variables = VariablesStack::new().resolve();
+
+        assert!(variables.as_map().is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({
+            "SERVERS": [{ "host": "a.example.com", "port": 80 }, { "host": "b.example.com", "port": 443 }],
+            "REGIONS": { "eu": "eu.example.com", "us": "us.example.com" },
+            "srv": "global",
+            "PORTS": [1, 2],
+            "ENV": "prod"
+        }))
+        .expect("Unable to parse json to variables")
+    }
+
+    #[test]
+    pub fn loop_array() {
+        let data = json!([
+            { "host": "first" },
+            { "$for": "srv in ${SERVERS}", "$do": { "host": "${srv.host}", "idx": "${$index}" } },
+            { "host": "last" }
+        ]);
+
+        assert_eq!(
+            variables().apply(data).expect("Unable to apply variables"),
+            json!([
+                { "host": "first" },
+                { "host": "a.example.com", "idx": 0 },
+                { "host": "b.example.com", "idx": 1 },
+                { "host": "last" }
+            ])
+        );
+    }
+
+    #[test]
+    pub fn loop_object() {
+        let data = json!({ "endpoints": { "$for": "url in REGIONS", "$do": "${$key}=https://${url}" } });
+
+        assert_eq!(
+            variables().apply(data).expect("Unable to apply variables"),
+            json!({ "endpoints": ["eu=https://eu.example.com", "us=https://us.example.com"] })
+        );
+    }
+
+    #[test]
+    pub fn loop_shadows_variables() {
+        let data = json!({ "hosts": { "$for": "srv in ${SERVERS}", "$do": "${srv.host}" }, "srv": "${srv}" });
+
+        assert_eq!(
+            variables().apply(data).expect("Unable to apply variables"),
+            json!({ "hosts": ["a.example.com", "b.example.com"], "srv": "global" })
+        );
+    }
+
+    #[test]
+    pub fn nested_loops_flatten() {
+        let data = json!({
+            "$for": "srv in ${SERVERS}",
+            "$do": {
+                "$for": "port in ${PORTS}",
+                "$do": "${srv.host}:${srv.port + port}"
+            }
+        });
+
+        assert_eq!(
+            variables().apply(data).expect("Unable to apply variables"),
+            json!(["a.example.com:81", "a.example.com:82", "b.example.com:444", "b.example.com:445"])
+        );
+    }
+
+    #[test]
+    pub fn nested_loop_field() {
+        let data = json!({
+            "$for": "srv in ${SERVERS}",
+            "$do": { "ports": { "$for": "port in ${PORTS}", "$do": "${srv.port + port}" } }
+        });
+
+        assert_eq!(
+            variables().apply(data).expect("Unable to apply variables"),
+            json!([{ "ports": [81, 82] }, { "ports": [444, 445] }])
+        );
+    }
+
+    #[test]
+    pub fn loop_with_conditions() {
+        let data = json!([
+            { "$for": "srv in ${SERVERS}", "$do": { "$when": "${srv.port == 443}", "host": "${srv.host}" } },
+            { "$when": "${ENV != 'prod'}", "$for": "srv in ${SERVERS}", "$do": "debug" },
+            { "$for": "srv in ${SERVERS}", "$do": { "$if": "${$index == 0}", "primary": "${srv.host}", "$else": "${srv.host}" } }
+        ]);
+
+        assert_eq!(
+            variables().apply(data).expect("Unable to apply variables"),
+            json!([{ "host": "b.example.com" }, { "primary": "a.example.com" }, "b.example.com"])
+        );
+    }
+
+    #[test]
+    pub fn loop_over_selection() {
+        let data = json!({ "$for": "host in ${SERVERS[*].host}", "$do": "${$index}:${host}" });
+
+        assert_eq!(variables().apply(data).expect("Unable to apply variables"), json!(["0:a.example.com", "1:b.example.com"]));
+    }
+
+    #[test]
+    pub fn loop_item_selectors() {
+        let data = json!({ "$for": "srv in ${SERVERS}", "$do": "${srv[host]}:${srv[port]}" });
+
+        assert_eq!(variables().apply(data).expect("Unable to apply variables"), json!(["a.example.com:80", "b.example.com:443"]));
+    }
+
+    #[test]
+    pub fn loop_item_undefined_path() {
+        let result = variables().apply(json!({ "$for": "ENV in ${SERVERS}", "$do": "${ENV.missing}" }));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "ENV.missing"));
+    }
+
+    #[rstest]
+    #[case(json!({ "$for": "srv in ${SERVERS}" }))]
+    #[case(json!({ "$for": "srv of ${SERVERS}", "$do": 1 }))]
+    #[case(json!({ "$for": " in ${SERVERS}", "$do": 1 }))]
+    #[case(json!({ "$for": "srv.host in ${SERVERS}", "$do": 1 }))]
+    #[case(json!({ "$for": "srv in ${ENV}", "$do": 1 }))]
+    #[case(json!({ "$for": 1, "$do": 1 }))]
+    #[case(json!({ "$for": "srv in ${SERVERS}", "$do": 1, "extra": 2 }))]
+    pub fn invalid_loop(#[case] data: Value) {
+        let result = variables().apply(data);
+
+        assert!(matches!(result, Err(Error::InvalidLoop(_))));
+    }
+
+    #[test]
+    pub fn loop_undefined_source() {
+        let result = variables().apply(json!({ "$for": "srv in ${MISSING}", "$do": 1 }));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == "MISSING"));
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Upstream {
+        host: String,
+        port: u16,
+        weight: u32
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Proxy {
+        upstreams: Vec<Upstream>
+    }
+
+    #[test]
+    pub fn derive_loop() {
+        let json = json!({
+            "variables": { "SERVERS": [{ "host": "a", "port": 80 }, { "host": "b", "port": 81 }] },
+            "proxy": {
+                "upstreams": {
+                    "$for": "srv in ${SERVERS}",
+                    "$do": { "host": "${srv.host}", "port": "${srv.port}", "weight": "${$index + 1}" }
+                }
+            }
+        });
+
+        let proxy: ProxyVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let proxy = proxy.parse().expect("Unable to set variables");
+
+        assert_eq!(proxy.upstreams.len(), 2);
+        assert_eq!(proxy.upstreams[1].host, "b");
+        assert_eq!(proxy.upstreams[1].port, 81);
+        assert_eq!(proxy.upstreams[1].weight, 2);
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({
+            "DOMAIN": "example.com",
+            "API_PORT": 8080,
+            "name": "global",
+            "NAMES": ["api", "web"],
+            "$macros": {
+                "service(name, port)": { "name": "${name}", "url": "http://${name}:${port}" },
+                "host(name)": "${name}.${DOMAIN}",
+                "endpoint(name)": { "host": "${host(name)}", "secure": "${name == 'api'}" },
+                "domain()": "${DOMAIN}",
+                "double(value)": "${value * 2}",
+                "loop(value)": "${loop(value)}",
+                "ping(value)": "${pong(value)}",
+                "pong(value)": "${ping(value)}"
+            }
+        }))
+        .expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case("${service('api', 8080)}", json!({ "name": "api", "url": "http://api:8080" }))]
+    #[case("${service('api', API_PORT)}", json!({ "name": "api", "url": "http://api:8080" }))]
+    #[case("${service(DOMAIN, API_PORT + 1)}", json!({ "name": "example.com", "url": "http://example.com:8081" }))]
+    #[case("${host('api')}", json!("api.example.com"))]
+    #[case("${endpoint('api')}", json!({ "host": "api.example.com", "secure": true }))]
+    #[case("${endpoint('web')}", json!({ "host": "web.example.com", "secure": false }))]
+    #[case("${domain()}", json!("example.com"))]
+    #[case("${double(21)}", json!(42))]
+    #[case("${double(double(2)) + 1}", json!(9))]
+    #[case("url: ${host('api')}", json!("url: api.example.com"))]
+    pub fn call(#[case] placeholder: &str, #[case] expected: Value) {
+        assert_eq!(variables().apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn parameters_shadow_variables() {
+        let value = variables().apply(json!({ "host": "${host('api')}", "name": "${name}" })).expect("Unable to apply variables");
+
+        assert_eq!(value, json!({ "host": "api.example.com", "name": "global" }));
+    }
+
+    #[test]
+    pub fn parameters_shadow_loop_variables() {
+        let data = json!({ "$for": "name in NAMES", "$do": "${host('db')}/${name}/${double($index)}" });
+
+        let value = variables().apply(data).expect("Unable to apply variables");
+
+        assert_eq!(value, json!(["db.example.com/api/0", "db.example.com/web/2"]));
+    }
+
+    #[rstest]
+    #[case("${service('api')}", "service", 2, 1)]
+    #[case("${host('a', 'b')}", "host", 1, 2)]
+    #[case("${domain(1)}", "domain", 0, 1)]
+    pub fn arity(#[case] placeholder: &str, #[case] name: &str, #[case] expected: usize, #[case] found: usize) {
+        let result = variables().apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::MacroArity(ref macro_name, e, f)) if macro_name == name && e == expected && f == found));
+    }
+
+    #[rstest]
+    #[case("${loop(1)}", "loop")]
+    #[case("${ping(1)}", "ping")]
+    pub fn recursion(#[case] placeholder: &str, #[case] name: &str) {
+        let result = variables().apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::RecursiveMacro(ref macro_name)) if macro_name == name));
+    }
+
+    #[test]
+    pub fn undefined_macro() {
+        let result = variables().apply(json!("${deploy('api')}"));
+
+        assert!(matches!(result, Err(Error::UndefinedMacro(ref name)) if name == "deploy"));
+    }
+
+    #[rstest]
+    #[case(json!({ "broken": 1 }))]
+    #[case(json!({ "broken(a": 1 }))]
+    #[case(json!({ "broken(a,)": 1 }))]
+    #[case(json!({ "broken(a b)": 1 }))]
+    pub fn invalid_signature(#[case] macros: Value) {
+        let variables = json_variables::from_json(json!({ "$macros": macros })).expect("Unable to parse json to variables");
+        let result = variables.apply(json!("${broken(1)}"));
+
+        assert!(matches!(result, Err(Error::InvalidMacro(_))));
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Service {
+        name: String,
+        url: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Deployment {
+        services: Vec<Service>
+    }
+
+    #[test]
+    pub fn derive_macros() {
+        let json = json!({
+            "variables": {
+                "$macros": { "service(name, port)": { "name": "${name}", "url": "http://${name}:${port}" } }
+            },
+            "deployment": {
+                "services": ["${service('api', 8080)}", "${service('web', 80)}"]
+            }
+        });
+
+        let deployment: DeploymentVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let deployment = deployment.parse().expect("Unable to set variables");
+
+        assert_eq!(deployment.services[0].url, "http://api:8080");
+        assert_eq!(deployment.services[1].name, "web");
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use json_variables::{Error, Variables};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({ "NAME": "John", "DB": { "host": "localhost", "hosts": ["a", "b"] } }))
+            .expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case("NAME", Some(json!("John")))]
+    #[case("DB.host", Some(json!("localhost")))]
+    #[case("DB.hosts.1", Some(json!("b")))]
+    #[case("DB.hosts.2", None)]
+    #[case("DB.port", None)]
+    #[case("NAME.first", None)]
+    pub fn get(#[case] path: &str, #[case] expected: Option<Value>) {
+        assert_eq!(variables().get_value(path), expected.as_ref());
+    }
+
+    #[rstest]
+    #[case("AGE", json!(23), None)]
+    #[case("NAME", json!("Jane"), Some(json!("John")))]
+    #[case("DB.port", json!(5432), None)]
+    #[case("DB.hosts.0", json!("c"), Some(json!("a")))]
+    #[case("DB.hosts.2", json!("c"), None)]
+    #[case("CACHE.redis.url", json!("redis://localhost"), None)]
+    pub fn insert(#[case] path: &str, #[case] value: Value, #[case] previous: Option<Value>) {
+        let mut variables = variables();
+        let result = variables.insert(path, value.clone()).expect("Unable to insert variable");
+
+        assert_eq!(result, previous);
+        assert!(variables.contains(path));
+        assert_eq!(variables.apply(json!(format!("${{{path}}}"))).expect("Unable to apply variables"), value);
+    }
+
+    #[rstest]
+    #[case("NAME.first")]
+    #[case("DB.hosts.3")]
+    #[case("DB.hosts.x.y")]
+    pub fn insert_invalid_path(#[case] path: &str) {
+        let result = variables().insert(path, json!(1));
+
+        assert!(matches!(result, Err(Error::InvalidPath(ref invalid)) if invalid == path));
+    }
+
+    #[rstest]
+    #[case("NAME", Some(json!("John")))]
+    #[case("DB.host", Some(json!("localhost")))]
+    #[case("DB.hosts.0", Some(json!("a")))]
+    #[case("DB.port", None)]
+    #[case("DB.hosts.5", None)]
+    pub fn remove(#[case] path: &str, #[case] removed: Option<Value>) {
+        let mut variables = variables();
+
+        assert_eq!(variables.remove(path), removed);
+        if removed.is_some() {
+            assert_ne!(variables.get_value(path), removed.as_ref());
+        }
+    }
+
+    #[test]
+    pub fn remove_updates_lookups() {
+        let mut variables = variables();
+        variables.remove("DB.host");
+
+        assert!(!variables.contains("DB.host"));
+        assert!(matches!(variables.apply(json!("${DB.host}")), Err(Error::UndefinedVariable(_))));
+    }
+
+    #[test]
+    pub fn get_mut() {
+        let mut variables = variables();
+        {
+            let mut host = variables.get_mut("DB").expect("Variable should be defined");
+            host["host"] = json!("example.com");
+            host["port"] = json!(5432);
+        }
+
+        assert_eq!(variables.replace(r#""${DB.host}:${DB.port}""#), r#""example.com:5432""#);
+        assert!(variables.get_mut("DB.url").is_none());
+    }
+
+    #[test]
+    pub fn extend() {
+        let mut variables = variables();
+        variables.extend([("AGE".to_string(), json!(23)), ("NAME".to_string(), json!("Jane"))]);
+
+        assert_eq!(variables.replace(r#""${NAME} ${AGE}""#), r#""Jane 23""#);
+    }
+
+    #[test]
+    pub fn from_iter() {
+        let variables: Variables = [("NAME".to_string(), json!({ "first": "John" }))].into_iter().collect();
+
+        assert!(variables.contains("NAME.first"));
+    }
+
+    #[test]
+    pub fn builder() {
+        let variables = Variables::builder()
+            .variables(json!({ "NAME": "John", "a.b": 1 }).as_object().unwrap().clone())
+            .variable("DB.host", "localhost")
+            .variable("DB.hosts", json!(["a"]))
+            .variable("DB.hosts.1", "b")
+            .pattern(r"\{\{([a-zA-Z0-9_.]+)\}\}")
+            .coerce(true)
+            .build()
+            .expect("Unable to build variables");
+
+        assert!(variables.coerce);
+        assert_eq!(variables.get_value("a.b"), None);
+        assert_eq!(variables.as_map().get("a.b"), Some(&json!(1)));
+        assert_eq!(variables.replace(r#""{{NAME}}@{{DB.host}}/{{DB.hosts.1}}""#), r#""John@localhost/b""#);
+    }
+
+    #[test]
+    pub fn builder_invalid_pattern() {
+        let result = Variables::builder().pattern("${(").build();
+
+        assert!(matches!(result, Err(Error::InvalidPattern(..))));
+    }
+}
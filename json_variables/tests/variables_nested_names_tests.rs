@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables, MAX_NESTING_DEPTH};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({
+            "ENV": "prod",
+            "REGION": "eu",
+            "INDEX": 1,
+            "DB_prod": "db.example.com",
+            "DB_dev": "localhost",
+            "REGIONS": { "eu": { "endpoint": "eu.example.com" }, "us": { "endpoint": "us.example.com" } },
+            "HOSTS": ["a", "b"],
+            "KEY": "ENV",
+            "ALIAS": "KEY"
+        }))
+        .expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case("${DB_${ENV}}", json!("db.example.com"))]
+    #[case("${REGIONS[${REGION}].endpoint}", json!("eu.example.com"))]
+    #[case("${REGIONS.${REGION}}", json!({ "endpoint": "eu.example.com" }))]
+    #[case("${HOSTS.${INDEX}}", json!("b"))]
+    #[case("${${KEY}}", json!("prod"))]
+    #[case("${${${ALIAS}}}", json!("prod"))]
+    #[case("${DB_${${KEY}}}", json!("db.example.com"))]
+    #[case("host: ${DB_${ENV}} (${ENV})", json!("host: db.example.com (prod)"))]
+    #[case("${DB_${ENV}}/${REGIONS[${REGION}].endpoint}", json!("db.example.com/eu.example.com"))]
+    pub fn nested_names(#[case] placeholder: &str, #[case] expected: Value) {
+        assert_eq!(variables().apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn nested_names_in_keys() {
+        let value = variables().apply(json!({ "${DB_${ENV}}": true })).expect("Unable to apply variables");
+
+        assert_eq!(value, json!({ "db.example.com": true }));
+    }
+
+    #[test]
+    pub fn nested_names_replace() {
+        let replaced = variables().replace(r#"{ "host": "${DB_${ENV}}", "url": "https://${REGIONS[${REGION}].endpoint}" }"#);
+
+        assert_eq!(serde_json::from_str::<Value>(&replaced).unwrap(), json!({ "host": "db.example.com", "url": "https://eu.example.com" }));
+    }
+
+    #[rstest]
+    #[case("${DB_${STAGE}}", "STAGE")]
+    #[case("${DB_${${MISSING}}}", "MISSING")]
+    #[case("${DB_${KEY}}", "DB_ENV")]
+    pub fn undefined_innermost(#[case] placeholder: &str, #[case] undefined: &str) {
+        let result = variables().apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref variable)) if variable == undefined));
+    }
+
+    #[test]
+    pub fn nesting_too_deep() {
+        let mut variables = variables();
+        variables.insert("V", json!("V")).expect("Unable to insert variable");
+
+        let placeholder = "${".repeat(MAX_NESTING_DEPTH + 2) + "V" + &"}".repeat(MAX_NESTING_DEPTH + 2);
+        let result = variables.apply(json!(placeholder));
+        assert!(matches!(result, Err(Error::NestingTooDeep(ref variable)) if variable == "${V}"));
+
+        let placeholder = "${".repeat(MAX_NESTING_DEPTH + 1) + "V" + &"}".repeat(MAX_NESTING_DEPTH + 1);
+        assert_eq!(variables.apply(json!(placeholder)).expect("Unable to apply variables"), json!("V"));
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Database {
+        host: String
+    }
+
+    #[test]
+    pub fn derive_nested_names() {
+        let json = json!({
+            "variables": { "ENV": "dev", "DB_dev": "localhost" },
+            "database": { "host": "${DB_${ENV}}" }
+        });
+
+        let database: DatabaseVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let database = database.parse().expect("Unable to set variables");
+
+        assert_eq!(database.host, "localhost");
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Map, Value};
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Server {
+        host: String,
+        port: u16
+    }
+
+    fn server_variables() -> ServerVariables {
+        let json = json!(
+        {
+            "variables": { "HOST": "localhost", "PORT": 8080, "DB": { "host": "db", "port": 5432 } },
+            "server": { "host": "${HOST}", "port": "${PORT}" }
+        });
+
+        serde_json::from_value(json).expect("Unable to parse json to variable")
+    }
+
+    #[rstest]
+    #[case(json!({}), "localhost", 8080)]
+    #[case(json!({ "HOST": "example.com" }), "example.com", 8080)]
+    #[case(json!({ "HOST": "example.com", "PORT": 80 }), "example.com", 80)]
+    #[case(json!({ "UNUSED": true }), "localhost", 8080)]
+    pub fn parse_with_map(#[case] overrides: Value, #[case] host: &str, #[case] port: u16) {
+        let overrides: Map<String, Value> = serde_json::from_value(overrides).unwrap();
+        let server = server_variables().parse_with_map(overrides).expect("Unable to set variables");
+
+        assert_eq!(server.host, host);
+        assert_eq!(server.port, port);
+    }
+
+    #[test]
+    pub fn parse_with() {
+        let overrides = json_variables::from_json(json!({ "PORT": 443 })).expect("Unable to parse json to variables");
+        let server = server_variables().parse_with(&overrides).expect("Unable to set variables");
+
+        assert_eq!(server.host, "localhost");
+        assert_eq!(server.port, 443);
+    }
+
+    #[test]
+    pub fn parse_with_defines_missing_variable() {
+        let json = json!(
+        {
+            "variables": { "HOST": "localhost" },
+            "server": { "host": "${HOST}", "port": "${PORT}" }
+        });
+        let server: ServerVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let overrides = json_variables::from_json(json!({ "PORT": 443 })).expect("Unable to parse json to variables");
+
+        let server = server.parse_with(&overrides).expect("Unable to set variables");
+        assert_eq!(server.port, 443);
+    }
+
+    /// Overrides replace a whole top level variable, nested values are not merged
+    #[test]
+    pub fn overrides_replace_whole_variable() {
+        let variables = json_variables::from_json(json!({ "DB": { "host": "db", "port": 5432 } })).unwrap();
+        let overrides = json!({ "DB": { "host": "example.com" } });
+        let variables = variables.with_overrides(overrides.as_object().unwrap());
+
+        assert!(variables.try_replace(r#""${DB.host}""#).is_ok());
+        assert!(variables.try_replace(r#""${DB.port}""#).is_err());
+    }
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables, Variables};
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({
+            "SERVERS": [
+                { "host": "a.example.com", "port": 80 },
+                { "host": "b.example.com", "port": 443 },
+                { "host": "c.example.com" }
+            ],
+            "LIST": [0, 1, 2, 3, 4],
+            "USERS": [
+                { "name": "John", "role": "admin", "team": { "id": 1 } },
+                { "name": "Jane", "role": "user", "team": { "id": 2 } },
+                { "name": "Anna", "role": "admin", "team": { "id": 2 } }
+            ],
+            "REGIONS": { "eu": { "url": "eu.example.com" }, "us": { "url": "us.example.com" } }
+        }))
+        .expect("Unable to parse json to variables")
+    }
+
+    #[rstest]
+    #[case("${SERVERS[*].host}", json!(["a.example.com", "b.example.com", "c.example.com"]))]
+    #[case("${SERVERS[*].port}", json!([80, 443]))]
+    #[case("${SERVERS[1].host}", json!("b.example.com"))]
+    #[case("${SERVERS[-1:]}", json!([{ "host": "c.example.com" }]))]
+    #[case("${LIST[1:3]}", json!([1, 2]))]
+    #[case("${LIST[:2]}", json!([0, 1]))]
+    #[case("${LIST[3:]}", json!([3, 4]))]
+    #[case("${LIST[-2:]}", json!([3, 4]))]
+    #[case("${LIST[3:1]}", json!([]))]
+    #[case("${LIST[2:100]}", json!([2, 3, 4]))]
+    #[case(r#"${USERS[?role=="admin"].name}"#, json!(["John", "Anna"]))]
+    #[case("${USERS[?role=='admin'].name}", json!(["John", "Anna"]))]
+    #[case(r#"${USERS[?role!="admin"].name}"#, json!(["Jane"]))]
+    #[case("${USERS[?team.id==2].name}", json!(["Jane", "Anna"]))]
+    #[case("${USERS[?@.team.id == 1].name}", json!(["John"]))]
+    #[case("${USERS[?role=='guest'].name}", json!([]))]
+    #[case("${REGIONS[*].url}", json!(["eu.example.com", "us.example.com"]))]
+    #[case("${REGIONS[eu].url}", json!("eu.example.com"))]
+    #[case("${REGIONS['us'].url}", json!("us.example.com"))]
+    #[case("${SERVERS[*].host[0:1]}", json!([]))]
+    pub fn select(#[case] placeholder: &str, #[case] expected: Value) {
+        assert_eq!(variables().apply(json!(placeholder)).expect("Unable to apply variables"), expected);
+    }
+
+    #[test]
+    pub fn select_inside_string() {
+        let value = variables().apply(json!("hosts: ${SERVERS[0:2].host}")).expect("Unable to apply variables");
+
+        assert_eq!(value, json!(r#"hosts: ["a.example.com","b.example.com"]"#));
+    }
+
+    #[test]
+    pub fn select_replace() {
+        let replaced = variables().replace(r#"{ "admins": "${USERS[?role==\"admin\"].name}" }"#);
+
+        assert_eq!(serde_json::from_str::<Value>(&replaced).unwrap(), json!({ "admins": ["John", "Anna"] }));
+    }
+
+    #[rstest]
+    #[case("${SERVERS[}")]
+    #[case("${SERVERS[1:x]}")]
+    #[case("${SERVERS[?host]}")]
+    #[case("${SERVERS[?==1]}")]
+    #[case("${SERVERS[1]host}")]
+    #[case("${[0]}")]
+    pub fn invalid_path(#[case] placeholder: &str) {
+        let result = variables().apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::InvalidPath(_))));
+    }
+
+    #[rstest]
+    #[case("${SERVERS[5].host}")]
+    #[case("${SERVERS[0].user}")]
+    #[case("${MISSING[*]}")]
+    #[case("${SERVERS[x]}")]
+    pub fn undefined(#[case] placeholder: &str) {
+        let result = variables().apply(json!(placeholder));
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(_))));
+    }
+
+    #[test]
+    pub fn get_selector() {
+        let variables = variables();
+
+        assert_eq!(variables.get::<Vec<u16>>("SERVERS[*].port").unwrap(), [80, 443]);
+        assert!(variables.contains("USERS[?role=='admin']"));
+        assert!(!variables.contains("USERS[?role=='admin'"));
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Proxy {
+        upstreams: Vec<String>,
+        admins: Vec<String>
+    }
+
+    #[test]
+    pub fn derive_select() {
+        let json = json!({
+            "variables": variables(),
+            "proxy": { "upstreams": "${SERVERS[*].host}", "admins": "${USERS[?role==\"admin\"].name}" }
+        });
+
+        let proxy: ProxyVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let proxy = proxy.parse().expect("Unable to set variables");
+
+        assert_eq!(proxy.upstreams, ["a.example.com", "b.example.com", "c.example.com"]);
+        assert_eq!(proxy.admins, ["John", "Anna"]);
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+    use json_variables::{Error, JsonVariables};
+
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Database {
+        host: String,
+        port: u16,
+        replicas: Vec<String>
+    }
+
+    fn variables() -> Value {
+        json!({
+            "DB": { "host": "localhost", "port": 5432, "replicas": ["a", "b"] },
+            "$profiles": {
+                "prod": { "DB": { "host": "db.example.com", "replicas": ["c"] } },
+                "dev": { "DB": { "port": 5433 } },
+                "empty": {}
+            }
+        })
+    }
+
+    #[rstest]
+    #[case("prod", "db.example.com", 5432, vec!["c"])]
+    #[case("dev", "localhost", 5433, vec!["a", "b"])]
+    #[case("empty", "localhost", 5432, vec!["a", "b"])]
+    pub fn parse_profile(#[case] profile: &str, #[case] host: &str, #[case] port: u16, #[case] replicas: Vec<&str>) {
+        let json = json!({
+            "variables": variables(),
+            "database": { "host": "${DB.host}", "port": "${DB.port}", "replicas": "${DB.replicas}" }
+        });
+
+        let database: DatabaseVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let database = database.parse_profile(profile).expect("Unable to set variables");
+
+        assert_eq!(database.host, host);
+        assert_eq!(database.port, port);
+        assert_eq!(database.replicas, replicas);
+    }
+
+    #[test]
+    pub fn parse_without_profile() {
+        let json = json!({
+            "variables": variables(),
+            "database": { "host": "${DB.host}", "port": "${DB.port}", "replicas": [] }
+        });
+
+        let database: DatabaseVariables = serde_json::from_value(json).expect("Unable to parse json to variable");
+        let database = database.parse().expect("Unable to set variables");
+
+        assert_eq!(database.host, "localhost");
+    }
+
+    #[test]
+    pub fn unknown_profile() {
+        let variables = json_variables::from_json(variables()).expect("Unable to parse json to variables");
+        let result = variables.with_profile("staging");
+
+        assert!(matches!(result, Err(Error::UnknownProfile(ref profile)) if profile == "staging"));
+    }
+
+    #[test]
+    pub fn no_profiles() {
+        let variables = json_variables::from_json(json!({ "NAME": "John" })).expect("Unable to parse json to variables");
+        let result = variables.with_profile("prod");
+
+        assert!(matches!(result, Err(Error::UnknownProfile(_))));
+    }
+
+    #[test]
+    pub fn profile_removes_profiles() {
+        let variables = json_variables::from_json(variables()).expect("Unable to parse json to variables");
+        let variables = variables.with_profile("dev").expect("Unable to select profile");
+
+        assert!(!variables.as_map().contains_key("$profiles"));
+        assert!(variables.contains("DB.replicas.1"));
+    }
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "schemars")]
+
+#[cfg(test)]
+mod tests {
+    use schemars::{schema_for, JsonSchema};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::JsonVariables;
+
+    #[derive(Debug, Deserialize, Serialize, JsonSchema)]
+    struct Country {
+        short: String
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonSchema, JsonVariables)]
+    struct Person {
+        name: String,
+        age: u8,
+        countries: Vec<Country>
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonSchema, JsonVariables)]
+    #[variables(flatten_data_field)]
+    struct Server {
+        port: u16
+    }
+
+    #[derive(Debug, Deserialize, Serialize, JsonSchema, JsonVariables)]
+    #[variables(flatten_data_field, pattern = r"\{\{([^{}]+)\}\}")]
+    struct Client {
+        port: u16
+    }
+
+    /// Structs which do not implement `JsonSchema` can still derive `JsonVariables`
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize, Serialize, JsonVariables)]
+    struct Plain {
+        name: String
+    }
+
+    fn placeholder() -> serde_json::Value {
+        json!({ "type": "string", "pattern": "^(?:\\$\\{([^{}]+)\\})$" })
+    }
+
+    #[test]
+    pub fn templated_schema() {
+        let schema = schema_for!(PersonVariables).to_value();
+
+        assert_eq!(schema["required"], json!(["person"]));
+        assert_eq!(schema["properties"]["variables"], json!({ "type": "object" }));
+
+        let person = &schema["properties"]["person"]["anyOf"];
+        assert_eq!(person[1], placeholder());
+
+        let properties = &person[0]["properties"];
+        assert_eq!(properties["name"]["anyOf"][0]["type"], "string");
+        assert_eq!(properties["name"]["anyOf"][1], placeholder());
+        assert_eq!(properties["age"]["anyOf"][1], placeholder());
+
+        let countries = &properties["countries"]["anyOf"][0];
+        assert_eq!(countries["type"], "array");
+        assert_eq!(countries["items"]["anyOf"][1], placeholder());
+        assert_eq!(countries["items"]["anyOf"][0]["properties"]["short"]["anyOf"][1], placeholder());
+    }
+
+    #[test]
+    pub fn templated_schema_flatten() {
+        let schema = schema_for!(ServerVariables).to_value();
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["variables"], json!({ "type": "object" }));
+        assert_eq!(schema["properties"]["port"]["anyOf"][1], placeholder());
+        assert_eq!(schema["required"], json!(["port"]));
+    }
+
+    #[test]
+    pub fn templated_schema_pattern() {
+        let schema = schema_for!(ClientVariables).to_value();
+
+        assert_eq!(schema["properties"]["port"]["anyOf"][1], json!({ "type": "string", "pattern": "^(?:\\{\\{([^{}]+)\\}\\})$" }));
+    }
+}
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use json_variables::{Error, Variables};
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Pool {
+        min: u32,
+        max: u32
+    }
+
+    #[derive(Debug, Serialize)]
+    struct Args {
+        env: String,
+        verbose: bool,
+        pool: Pool
+    }
+
+    fn variables() -> Variables {
+        json_variables::from_json(json!({
+            "NAME": "John",
+            "DB": { "pool": { "min": 1, "max": 10 }, "hosts": ["a", "b"], "timeout": 2.5 }
+        }))
+        .expect("Unable to parse json to variables")
+    }
+
+    #[test]
+    pub fn get() {
+        let variables = variables();
+
+        assert_eq!(variables.get::<String>("NAME").unwrap(), "John");
+        assert_eq!(variables.get::<Pool>("DB.pool").unwrap(), Pool { min: 1, max: 10 });
+        assert_eq!(variables.get::<u32>("DB.pool.max").unwrap(), 10);
+        assert_eq!(variables.get::<Vec<String>>("DB.hosts").unwrap(), ["a", "b"]);
+        assert_eq!(variables.get::<String>("DB.hosts.1").unwrap(), "b");
+        assert_eq!(variables.get::<f64>("DB.timeout").unwrap(), 2.5);
+        assert_eq!(variables.get::<Option<u32>>("DB.pool.min").unwrap(), Some(1));
+    }
+
+    #[test]
+    pub fn get_undefined() {
+        let result = variables().get::<u32>("DB.port");
+
+        assert!(matches!(result, Err(Error::UndefinedVariable(ref path)) if path == "DB.port"));
+    }
+
+    #[test]
+    pub fn get_invalid_type() {
+        let err = variables().get::<u32>("DB.hosts").expect_err("An array should not deserialize into a number");
+
+        assert!(matches!(err, Error::InvalidVariable(ref path, _) if path == "DB.hosts"));
+        assert!(err.to_string().contains("DB.hosts"));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    pub fn from_serialize() {
+        let args = Args { env: "prod".to_string(), verbose: true, pool: Pool { min: 2, max: 20 } };
+        let variables = Variables::from_serialize(&args).expect("Unable to create variables");
+
+        assert_eq!(variables.get::<String>("env").unwrap(), "prod");
+        assert!(variables.get::<bool>("verbose").unwrap());
+        assert_eq!(variables.get::<Pool>("pool").unwrap(), Pool { min: 2, max: 20 });
+        assert_eq!(variables.replace(r#""${env}-${pool.max}""#), r#""prod-20""#);
+    }
+
+    #[test]
+    pub fn from_serialize_not_an_object() {
+        let result = Variables::from_serialize(&vec![1, 2, 3]);
+
+        assert!(matches!(result, Err(Error::InvalidJson)));
+    }
+}